@@ -0,0 +1,11 @@
+#import bevy_pbr::forward_io::VertexOutput
+
+@group(2) @binding(0) var<uniform> flash: vec4<f32>;
+@group(2) @binding(1) var color_texture: texture_2d<f32>;
+@group(2) @binding(2) var color_sampler: sampler;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(color_texture, color_sampler, in.uv);
+    return vec4(mix(color.rgb, flash.rgb, flash.a), color.a);
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use pixelate_mesh::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        // Custom canvas materials need their own material plugin
+        .add_plugins(MaterialPlugin::<HitFlashMaterial>::default())
+        .add_plugins(PixelateMeshPlugin::<MainCamera, HitFlashMaterial>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, flash)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+/// Shows the pixelated cube and periodically flashes it white.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct HitFlashMaterial {
+    /// The color to flash in, with the alpha as the strength of the flash.
+    #[uniform(0)]
+    flash: LinearRgba,
+    #[texture(1)]
+    #[sampler(2)]
+    image: Handle<Image>,
+    alpha_mode: AlphaMode,
+}
+
+impl Material for HitFlashMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/hit_flash.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+}
+
+impl CanvasMaterial for HitFlashMaterial {
+    fn from_canvas_image(image: Handle<Image>, _target: Entity, pixelate: &Pixelate) -> Self {
+        Self {
+            flash: LinearRgba::NONE,
+            image,
+            alpha_mode: pixelate.alpha_mode.into(),
+        }
+    }
+
+    // Keeps the flash going when the canvas gets a new image
+    fn set_canvas_image(&mut self, image: Handle<Image>, _target: Entity, pixelate: &Pixelate) {
+        self.image = image;
+        self.alpha_mode = pixelate.alpha_mode.into();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Name::new("Cube"),
+        Pixelate::splat(64),
+        Mesh3d(meshes.add(Mesh::from(Cuboid::default()))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::srgb(0.8, 0.2, 0.2)))),
+    ));
+
+    commands.spawn((
+        Name::new("Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        PointLight::default(),
+        Transform::from_translation(Vec3::new(0.0, 10.0, 10.0)),
+        PIXELATION_RENDER_LAYERS.clone(),
+    ));
+}
+
+fn flash(time: Res<Time>, mut materials: ResMut<Assets<HitFlashMaterial>>) {
+    // Flash for a fifth of every second
+    let strength = if time.elapsed_secs().fract() < 0.2 {
+        1.0
+    } else {
+        0.0
+    };
+    for (_, material) in materials.iter_mut() {
+        material.flash = LinearRgba::WHITE.with_alpha(strength);
+    }
+}
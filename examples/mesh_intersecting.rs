@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use pixelate_mesh::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PixelateMeshPlugin::<MainCamera>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Component)]
+struct Rotating;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Name::new("Cube"),
+        Rotating,
+        // The cube sinks into the floor and pokes through the wall, which is only visible with depth compositing.
+        Pixelate::splat(64).with_depth_compositing(),
+        Mesh3d(meshes.add(Mesh::from(Cuboid::default()))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::WHITE))),
+        Transform::from_xyz(0.0, 0.2, 0.0),
+    ));
+
+    commands.spawn((
+        Name::new("Floor"),
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(5.0, 5.0))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::srgb(0.3, 0.5, 0.3)))),
+    ));
+
+    commands.spawn((
+        Name::new("Wall"),
+        Mesh3d(meshes.add(Cuboid::new(0.1, 2.0, 3.0))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::srgb(0.6, 0.3, 0.3)))),
+        Transform::from_xyz(0.3, 1.0, 0.0),
+    ));
+
+    commands.spawn((
+        Name::new("Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        PointLight::default(),
+        Transform::from_translation(Vec3::new(0.0, 10.0, 10.0)),
+        // Light both the pixelated cube and the rest of the world.
+        RenderLayers::default().union(&PIXELATION_RENDER_LAYERS),
+    ));
+}
+
+fn rotate(time: Res<Time>, mut query: Query<&mut Transform, With<Rotating>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(time.delta_secs() * 0.5);
+    }
+}
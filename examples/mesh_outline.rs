@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use pixelate_mesh::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PixelateMeshPlugin::<MainCamera>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Component)]
+struct Rotating;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Name::new("Cube"),
+        Pixelate::splat(64),
+        PixelOutline::new(Color::BLACK),
+        Rotating,
+        Mesh3d(meshes.add(Mesh::from(Cuboid::default()))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::srgb(0.9, 0.6, 0.3)))),
+        Transform::from_xyz(-1.5, 0.0, 0.0),
+    ));
+
+    commands.spawn((
+        Name::new("Torus"),
+        Pixelate::splat(64),
+        PixelOutline::new(Color::srgb(0.1, 0.05, 0.2))
+            .with_placement(OutlinePlacement::Inner)
+            .with_inner_edges(InnerEdges::Normal { threshold: 0.3 }),
+        Rotating,
+        Mesh3d(meshes.add(Mesh::from(Torus::default()))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::srgb(0.4, 0.7, 0.9)))),
+        Transform::from_xyz(1.5, 0.0, 0.0),
+    ));
+
+    commands.spawn((
+        Name::new("Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        PointLight::default(),
+        Transform::from_translation(Vec3::new(0.0, 10.0, 10.0)),
+        PIXELATION_RENDER_LAYERS.clone(),
+    ));
+}
+
+fn rotate(time: Res<Time>, mut query: Query<&mut Transform, With<Rotating>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(time.delta_secs() * 0.5);
+        transform.rotate_x(time.delta_secs() * 0.3);
+    }
+}
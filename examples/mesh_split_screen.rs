@@ -0,0 +1,78 @@
+use bevy::{prelude::*, render::camera::Viewport, window::WindowResized};
+use pixelate_mesh::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PixelateMeshPlugin::<MainCamera>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, set_camera_viewports)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Component)]
+struct RightCamera;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Name::new("Cube"),
+        Pixelate::splat(64),
+        Mesh3d(meshes.add(Mesh::from(Cuboid::default()))),
+        MeshMaterial3d(materials.add(StandardMaterial::from(Color::WHITE))),
+    ));
+
+    commands.spawn((
+        Name::new("Left Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Right Camera"),
+        MainCamera,
+        RightCamera,
+        Camera3d::default(),
+        Camera {
+            order: 1,
+            clear_color: ClearColorConfig::None,
+            ..default()
+        },
+        Transform::from_xyz(3.0, 1.0, -4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        PointLight::default(),
+        Transform::from_translation(Vec3::new(0.0, 10.0, 10.0)),
+        PIXELATION_RENDER_LAYERS.clone(),
+    ));
+}
+
+fn set_camera_viewports(
+    windows: Query<&Window>,
+    mut resize_events: EventReader<WindowResized>,
+    mut cameras: Query<(&mut Camera, Has<RightCamera>), With<MainCamera>>,
+) {
+    for resize_event in resize_events.read() {
+        let Ok(window) = windows.get(resize_event.window) else {
+            continue;
+        };
+        let size = window.physical_size();
+        let half_width = size.x / 2;
+        for (mut camera, is_right) in &mut cameras {
+            camera.viewport = Some(Viewport {
+                physical_position: UVec2::new(if is_right { half_width } else { 0 }, 0),
+                physical_size: UVec2::new(half_width, size.y),
+                ..default()
+            });
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use pixelate_mesh::prelude::*;
+use std::f32::consts::PI;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PixelateMeshPlugin::<MainCamera>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, play_animation)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Resource)]
+struct RunAnimation {
+    graph: Handle<AnimationGraph>,
+    index: AnimationNodeIndex,
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+) {
+    let (graph, index) = AnimationGraph::from_clip(
+        asset_server.load(GltfAssetLabel::Animation(2).from_asset("Fox.glb")),
+    );
+    commands.insert_resource(RunAnimation {
+        graph: graphs.add(graph),
+        index,
+    });
+
+    commands.spawn((
+        Name::new("Fox"),
+        // The run cycle moves the legs and tail outside of the bind pose,
+        // so track the joints to keep them on the canvas.
+        Pixelate::splat(128).with_bounds(BoundsMode::Joints { padding: 15.0 }),
+        SceneRoot(asset_server.load("Fox.glb#Scene0")),
+    ));
+
+    commands.spawn((
+        Name::new("Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(100.0, 100.0, 150.0).looking_at(Vec3::new(0.0, 20.0, 0.0), Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -PI / 4.)),
+        PIXELATION_RENDER_LAYERS.clone(),
+    ));
+}
+
+fn play_animation(
+    mut commands: Commands,
+    run_animation: Res<RunAnimation>,
+    mut players: Query<(Entity, &mut AnimationPlayer), Added<AnimationPlayer>>,
+) {
+    for (entity, mut player) in &mut players {
+        player.play(run_animation.index).repeat();
+        commands
+            .entity(entity)
+            .insert(AnimationGraphHandle(run_animation.graph.clone()));
+    }
+}
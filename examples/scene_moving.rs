@@ -17,7 +17,8 @@ struct MainCamera;
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         Name::new("Fox"),
-        Pixelate::splat(128),
+        // Keep the pixels from crawling while the fox moves
+        Pixelate::splat(128).with_texel_snapping(),
         SceneRoot(asset_server.load("Fox.glb#Scene0")),
     ));
 
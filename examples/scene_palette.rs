@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use pixelate_mesh::prelude::*;
+use std::f32::consts::PI;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PixelateMeshPlugin::<MainCamera>::default())
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut palettes: ResMut<Assets<PixelPalette>>,
+) {
+    commands.spawn((
+        Name::new("Fox with loaded palette"),
+        Pixelate::splat(128),
+        // Palettes can be loaded from .gpl, .hex and .palette.png files
+        PixelPaletteHandle(asset_server.load("palettes/autumn.gpl")),
+        SceneRoot(asset_server.load("Fox.glb#Scene0")),
+    ));
+
+    commands.spawn((
+        Name::new("Fox with custom palette"),
+        Pixelate::splat(128),
+        // ...or be created in code
+        PixelPaletteHandle(palettes.add(PixelPalette::new([
+            Color::BLACK,
+            Color::srgb(0.4, 0.2, 0.1),
+            Color::srgb(0.9, 0.5, 0.2),
+            Color::WHITE,
+        ]))),
+        SceneRoot(asset_server.load("Fox.glb#Scene0")),
+        Transform::from_xyz(60.0, 0.0, -60.0),
+    ));
+
+    commands.spawn((
+        Name::new("Camera"),
+        MainCamera,
+        Camera3d::default(),
+        Transform::from_xyz(100.0, 100.0, 150.0).looking_at(Vec3::new(0.0, 20.0, 0.0), Vec3::Y),
+    ));
+
+    commands.spawn((
+        Name::new("Light"),
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_rotation(Quat::from_euler(EulerRot::ZYX, 0.0, 1.0, -PI / 4.)),
+        PIXELATION_RENDER_LAYERS.clone(),
+    ));
+}
@@ -0,0 +1,57 @@
+use crate::shadow::PixelationShadow;
+use crate::{BoundsMode, Pixelate, PixelationBounds};
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::primitives::Aabb;
+
+/// The bounds of all meshes of a target as computed when it became ready, in its local space.
+#[derive(Debug, Component, Copy, Clone, Deref)]
+pub(crate) struct MeshBounds(pub(crate) Aabb);
+
+pub(crate) fn update_bounds(
+    mut target_query: Query<(
+        Entity,
+        &Pixelate,
+        &MeshBounds,
+        &GlobalTransform,
+        &mut PixelationBounds,
+    )>,
+    children: Query<&Children>,
+    shadows: Query<(), With<PixelationShadow>>,
+    skinned_meshes: Query<&SkinnedMesh>,
+    global_transforms: Query<&GlobalTransform>,
+) {
+    for (entity, pixelate, mesh_bounds, global_transform, mut bounds) in &mut target_query {
+        let aabb = match pixelate.bounds {
+            BoundsMode::Static => **mesh_bounds,
+            BoundsMode::Override(aabb) => aabb,
+            BoundsMode::Joints { padding } => {
+                let world_to_local = global_transform.affine().inverse();
+                let mut stack = vec![entity];
+                let mut min = Vec3::MAX;
+                let mut max = Vec3::MIN;
+                while let Some(current) = stack.pop() {
+                    if shadows.contains(current) {
+                        continue;
+                    }
+                    if let Ok(skinned_mesh) = skinned_meshes.get(current) {
+                        for joint in global_transforms.iter_many(&skinned_mesh.joints) {
+                            let position = world_to_local.transform_point3(joint.translation());
+                            min = min.min(position);
+                            max = max.max(position);
+                        }
+                    }
+                    if let Ok(current_children) = children.get(current) {
+                        stack.extend(current_children.iter());
+                    }
+                }
+                if min.cmple(max).all() {
+                    Aabb::from_min_max(min - padding, max + padding)
+                } else {
+                    **mesh_bounds
+                }
+            }
+        };
+        bounds.set_if_neq(PixelationBounds(aabb));
+    }
+}
@@ -1,22 +1,33 @@
+use crate::bounds::MeshBounds;
+use crate::depth::{create_depth_image, CanvasDepthMaterial, PixelationDepth, DEPTH_IMAGE_FORMAT};
+use crate::layers::{replace_render_layers, AllocatedLayers};
+use crate::pool::RenderTargetPool;
 use crate::ready_checks::PixelationTargetReadyEvent;
-use crate::util::get_max_radius;
-use crate::{Canvas, Pixelate, PixelationCamera, PIXELATION_RENDER_LAYERS};
+use crate::{
+    Canvas, CanvasMaterial, Pixelate, PixelationBounds, PixelationCamera, PixelationLayers,
+    PixelationRenderLayers,
+};
+use bevy::core_pipeline::core_3d::Camera3dDepthTextureUsage;
+use bevy::ecs::system::EntityCommands;
 use bevy::image::ImageSampler;
 use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
-use bevy::render::mesh::MeshAabb as _;
 use bevy::{
     prelude::*,
     render::{
         camera::ClearColorConfig,
         camera::RenderTarget,
-        primitives::Aabb,
         render_resource::{
             Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
         },
+        view::RenderLayers,
     },
 };
 use std::f32::consts::PI;
 
+/// The quad shared by all canvases, see [`create_canvas_mesh`].
+#[derive(Debug, Clone, Resource, Deref)]
+pub(crate) struct CanvasMeshHandle(Handle<Mesh>);
+
 #[derive(Debug, Resource, Clone, Default)]
 pub(crate) struct Ordering {
     pub(crate) last_order: isize,
@@ -31,79 +42,220 @@ impl Ordering {
 
 pub(crate) fn add_pixelation(
     mut commands: Commands,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut images: ResMut<Assets<Image>>,
-    pixelate_query: Query<&Pixelate>,
     mut pixelation_target_ready_reader: EventReader<PixelationTargetReadyEvent>,
-    mut ordering: ResMut<Ordering>,
+    mut allocated_layers: ResMut<AllocatedLayers>,
+    layer_config: Res<PixelationLayers>,
+    render_layers_query: Query<&RenderLayers>,
+    pixelated_query: Query<(), With<Pixelate>>,
 ) {
     for event in pixelation_target_ready_reader.read() {
         for (&entity, target) in event.iter() {
-            debug!("Spawning canvas");
-            let mesh = meshes.get(&target.mesh_handle).unwrap();
-            let aabb = mesh.compute_aabb().unwrap();
-            let plane_handle = meshes.add(create_canvas_mesh(&aabb));
-            let pixelate = pixelate_query.get(entity).unwrap();
-            let image = create_canvas_image(*pixelate);
-            let image_handle = images.add(image);
-            commands
-                .entity(entity)
-                .insert((PIXELATION_RENDER_LAYERS.clone(), aabb));
-            commands.spawn((
-                Name::new("Pixelation Camera"),
-                Camera {
-                    order: ordering.next(),
-                    target: RenderTarget::Image(image_handle.clone().into()),
-                    clear_color: ClearColorConfig::Custom(Color::NONE),
-                    msaa_writeback: false,
-                    ..default()
-                },
-                Camera3d::default(),
-                PixelationCamera { target: entity },
-                PIXELATION_RENDER_LAYERS.clone(),
+            // The event may be read a frame late, after the target was despawned or lost `Pixelate`.
+            if !pixelated_query.contains(entity) {
+                continue;
+            }
+            debug!("Marking target as pixelated");
+            let render_layers = allocated_layers.target_layers(entity, &layer_config);
+            commands.entity(entity).try_insert((
+                PixelationRenderLayers(render_layers.clone()),
+                PixelationBounds(target.aabb),
+                MeshBounds(target.aabb),
             ));
+            replace_render_layers(
+                &mut commands,
+                entity,
+                entity,
+                render_layers_query.get(entity).ok(),
+                render_layers,
+            );
+        }
+    }
+}
+
+/// Spawns the canvas and pixelation camera that show `target` to `main_camera`.
+/// The canvas is only visible on the given `canvas_layer`, which only `main_camera` renders.
+pub(crate) fn spawn_pixelation_pair<M: CanvasMaterial>(
+    commands: &mut Commands,
+    materials: &mut Assets<M>,
+    depth_materials: &mut Assets<CanvasDepthMaterial>,
+    canvas_mesh: &CanvasMeshHandle,
+    images: &mut Assets<Image>,
+    pool: &mut RenderTargetPool,
+    ordering: &mut Ordering,
+    target: Entity,
+    pixelate: &Pixelate,
+    image_size: UVec2,
+    target_layers: &RenderLayers,
+    main_camera: Entity,
+    canvas_layer: usize,
+) {
+    debug!("Spawning canvas");
+    let image_handle = pool.acquire(images, image_size, CANVAS_IMAGE_FORMAT, create_canvas_image);
+    // Reserved up front, so that the canvas and the pixelation camera can refer to each other.
+    let canvas = commands.spawn_empty().id();
+    let pixelation_camera = commands
+        .spawn((
+            Name::new("Pixelation Camera"),
+            Camera {
+                order: ordering.next(),
+                target: RenderTarget::Image(image_handle.clone().into()),
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                msaa_writeback: false,
+                ..default()
+            },
+            Camera3d {
+                // Allows copying the depth for `Pixelate::depth_compositing`.
+                depth_texture_usages: Camera3dDepthTextureUsage::from(
+                    TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+                ),
+                ..default()
+            },
+            PixelationCamera {
+                target,
+                main_camera,
+                canvas,
+                canvas_offset: Vec3::ZERO,
+                tight_frame: None,
+                canvas_size: None,
+            },
+            target_layers.clone(),
+        ))
+        .id();
+
+    let mut canvas_mesh = commands.spawn((
+        Name::new("Canvas Mesh"),
+        Mesh3d(canvas_mesh.0.clone()),
+        Transform::from_rotation(Quat::from_rotation_y(PI)),
+        RenderLayers::layer(canvas_layer),
+        NotShadowCaster,
+        NotShadowReceiver,
+    ));
+    let canvas_mesh_entity = canvas_mesh.id();
+    if let Some(depth) = update_canvas_material(
+        &mut canvas_mesh,
+        None,
+        None,
+        target,
+        image_handle,
+        image_size,
+        pixelate,
+        images,
+        pool,
+        materials,
+        depth_materials,
+    ) {
+        commands.entity(pixelation_camera).insert(depth);
+    }
+
+    commands
+        .entity(canvas)
+        .insert((
+            Name::new("Canvas"),
+            Canvas {
+                target,
+                main_camera,
+                pixelation_camera,
+            },
+            Transform::default(),
+            Visibility::default(),
+        ))
+        .add_child(canvas_mesh_entity);
+}
 
-            commands
-                .spawn((
-                    Name::new("Canvas"),
-                    Canvas { target: entity },
-                    Transform::default(),
-                    Visibility::default(),
-                ))
-                .with_children(|parent| {
-                    parent.spawn((
-                        Name::new("Canvas Mesh"),
-                        Mesh3d(plane_handle),
-                        MeshMaterial3d(materials.add(create_canvas_material(image_handle))),
-                        Transform::from_rotation(Quat::from_rotation_y(PI)),
-                        NotShadowCaster,
-                        NotShadowReceiver,
-                    ));
-                });
+/// Makes the material of the canvas mesh of `target` show `image_handle`.
+/// The current material is updated if it is of the right kind, otherwise it is replaced.
+/// With [`Pixelate::depth_compositing`], this also acquires the texture that the pixelation camera's depth is copied into,
+/// which the returned [`PixelationDepth`] and [`Msaa::Off`] need to be inserted on the pixelation camera for.
+pub(crate) fn update_canvas_material<M: CanvasMaterial>(
+    canvas_mesh: &mut EntityCommands,
+    current_material: Option<&MeshMaterial3d<M>>,
+    current_depth_material: Option<&MeshMaterial3d<CanvasDepthMaterial>>,
+    target: Entity,
+    image_handle: Handle<Image>,
+    image_size: UVec2,
+    pixelate: &Pixelate,
+    images: &mut Assets<Image>,
+    pool: &mut RenderTargetPool,
+    materials: &mut Assets<M>,
+    depth_materials: &mut Assets<CanvasDepthMaterial>,
+) -> Option<(PixelationDepth, Msaa)> {
+    if !pixelate.depth_compositing {
+        match current_material.and_then(|material| materials.get_mut(material)) {
+            Some(material) => material.set_canvas_image(image_handle, target, pixelate),
+            None => {
+                canvas_mesh
+                    .remove::<MeshMaterial3d<CanvasDepthMaterial>>()
+                    .insert(MeshMaterial3d(materials.add(M::from_canvas_image(
+                        image_handle,
+                        target,
+                        pixelate,
+                    ))));
+            }
         }
+        return None;
     }
+    let depth_handle = pool.acquire(images, image_size, DEPTH_IMAGE_FORMAT, create_depth_image);
+    let material =
+        CanvasDepthMaterial::new(image_handle, depth_handle.clone(), pixelate.alpha_mode);
+    let material_handle = match current_depth_material {
+        Some(current) if depth_materials.contains(current) => {
+            if let Some(current_material) = depth_materials.get_mut(current) {
+                // Keep the matrix, which is only updated in `PostUpdate`.
+                *current_material = CanvasDepthMaterial {
+                    world_from_pixelation_clip: current_material.world_from_pixelation_clip,
+                    ..material
+                };
+            }
+            current.0.clone()
+        }
+        _ => {
+            let material_handle = depth_materials.add(material);
+            canvas_mesh
+                .remove::<MeshMaterial3d<M>>()
+                .insert(MeshMaterial3d(material_handle.clone()));
+            material_handle
+        }
+    };
+    // The depth texture can only be copied if it is not multisampled.
+    Some((
+        PixelationDepth {
+            texture: depth_handle,
+            material: material_handle,
+        },
+        Msaa::Off,
+    ))
 }
 
-fn create_canvas_mesh(aabb: &Aabb) -> Mesh {
-    let radius = get_max_radius(aabb);
-    let size = Vec2::splat(radius * 2.);
-    Mesh::from(Rectangle::from_size(size))
+/// Creates a 2 × 2 quad, which `position_canvas` scales to the size of the target in world space every frame,
+/// so the mesh never has to be rebuilt when the target or its bounds change size.
+pub(crate) fn create_canvas_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let handle = meshes.add(Rectangle::from_size(Vec2::splat(2.)));
+    commands.insert_resource(CanvasMeshHandle(handle));
 }
 
-pub(crate) fn create_canvas_material(image_handle: Handle<Image>) -> StandardMaterial {
-    StandardMaterial {
-        base_color_texture: Some(image_handle),
-        unlit: true,
-        alpha_mode: AlphaMode::Blend,
-        ..default()
+impl CanvasMaterial for StandardMaterial {
+    fn from_canvas_image(image: Handle<Image>, _target: Entity, pixelate: &Pixelate) -> Self {
+        StandardMaterial {
+            base_color_texture: Some(image),
+            unlit: true,
+            alpha_mode: pixelate.alpha_mode.into(),
+            ..default()
+        }
+    }
+
+    fn set_canvas_image(&mut self, image: Handle<Image>, _target: Entity, pixelate: &Pixelate) {
+        self.base_color_texture = Some(image);
+        self.alpha_mode = pixelate.alpha_mode.into();
     }
 }
 
-pub(crate) fn create_canvas_image(pixelate: Pixelate) -> Image {
+/// The format of the textures that pixelation cameras render to.
+pub(crate) const CANVAS_IMAGE_FORMAT: TextureFormat = TextureFormat::Bgra8UnormSrgb;
+
+pub(crate) fn create_canvas_image(size: UVec2) -> Image {
     let size = Extent3d {
-        width: pixelate.horizontal_pixels,
-        height: pixelate.vertical_pixels,
+        width: size.x,
+        height: size.y,
         ..default()
     };
     // This is the texture that will be rendered to.
@@ -112,7 +264,7 @@ pub(crate) fn create_canvas_image(pixelate: Pixelate) -> Image {
             label: Some("Pixelation texture"),
             size,
             dimension: TextureDimension::D2,
-            format: TextureFormat::Bgra8UnormSrgb,
+            format: CANVAS_IMAGE_FORMAT,
             mip_level_count: 1,
             sample_count: 1,
             usage: TextureUsages::TEXTURE_BINDING
@@ -0,0 +1,409 @@
+use crate::util::{get_max_radius, get_world_center, get_world_scale};
+use crate::{PixelDensity, Pixelate};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+/// Derived texture sizes are rounded up to a multiple of this,
+/// so that the render texture is not reallocated every time the size changes slightly.
+const SIZE_BUCKET: u32 = 8;
+
+/// Derived texture sizes never exceed this, even when the camera is right in front of the target.
+const MAX_SIZE: u32 = 4096;
+
+/// Computes the size of the render texture for a target as seen by a main camera.
+/// Derived sizes cover `tight_frame` if the target uses [`CanvasFraming::Tight`](crate::CanvasFraming::Tight),
+/// and the target's bounding sphere otherwise.
+pub(crate) fn canvas_image_size(
+    pixelate: &Pixelate,
+    bounds: &Aabb,
+    target_transform: &GlobalTransform,
+    main_camera: Option<(&Camera, &Projection, &GlobalTransform)>,
+    tight_frame: Option<Vec2>,
+) -> UVec2 {
+    let fixed_size =
+        UVec2::new(pixelate.horizontal_pixels, pixelate.vertical_pixels).max(UVec2::ONE);
+    let radius = get_max_radius(bounds);
+    let scale = get_world_scale(target_transform);
+    let frame = tight_frame.unwrap_or(Vec2::splat(radius * scale * 2.));
+    let pixels = match pixelate.density {
+        PixelDensity::Fixed => return fixed_size,
+        PixelDensity::WorldSpace { pixels_per_unit } => frame * pixels_per_unit,
+        PixelDensity::ScreenSpace {
+            screen_pixels_per_pixel,
+        } => {
+            let Some((camera, projection, camera_transform)) = main_camera else {
+                return fixed_size;
+            };
+            let distance = camera_transform
+                .translation()
+                .distance(get_world_center(bounds, target_transform));
+            let Some(screen_pixels) = get_screen_size(1., distance, camera, projection) else {
+                return fixed_size;
+            };
+            frame * screen_pixels / screen_pixels_per_pixel.max(f32::EPSILON)
+        }
+        PixelDensity::PixelPerfect {
+            screen_pixels_per_pixel,
+        } => {
+            let Some((camera, projection, camera_transform)) = main_camera else {
+                return fixed_size;
+            };
+            let distance = camera_transform
+                .translation()
+                .distance(get_world_center(bounds, target_transform));
+            if let Some(frame) = tight_frame {
+                // The canvas is grown to fit these sizes by `get_tight_texel_size`.
+                let Some(screen_pixels) =
+                    get_screen_size(1., distance - radius * scale, camera, projection)
+                else {
+                    return fixed_size;
+                };
+                return to_bucket_size(
+                    frame * screen_pixels / screen_pixels_per_pixel.max(1) as f32,
+                );
+            }
+            let Some((size, _)) = get_pixel_perfect_layout(
+                screen_pixels_per_pixel,
+                radius,
+                scale,
+                distance,
+                camera,
+                projection,
+            ) else {
+                return fixed_size;
+            };
+            return UVec2::splat(size);
+        }
+    };
+    to_bucket_size(pixels)
+}
+
+/// Returns the world-space size of one texel at the target for [`CanvasFraming::Tight`](crate::CanvasFraming::Tight),
+/// such that `frame` fits into an image of `image_size` with square texels.
+/// For [`PixelDensity::PixelPerfect`], texels are grown to cover exactly `screen_pixels_per_pixel` screen pixels.
+pub(crate) fn get_tight_texel_size(
+    pixelate: &Pixelate,
+    bounds: &Aabb,
+    frame: Vec2,
+    image_size: UVec2,
+    target_transform: &GlobalTransform,
+    camera: &Camera,
+    projection: &Projection,
+    camera_transform: &GlobalTransform,
+) -> f32 {
+    let fitted = (frame / image_size.max(UVec2::ONE).as_vec2()).max_element();
+    let PixelDensity::PixelPerfect {
+        screen_pixels_per_pixel,
+    } = pixelate.density
+    else {
+        return fitted;
+    };
+    let distance = camera_transform
+        .translation()
+        .distance(get_world_center(bounds, target_transform));
+    let canvas_distance = distance - get_max_radius(bounds) * get_world_scale(target_transform);
+    get_screen_size(1., canvas_distance, camera, projection)
+        .filter(|screen_pixels| *screen_pixels > 0.)
+        // While the image is being resized, it may be too small for the exact size.
+        .map_or(fitted, |screen_pixels| {
+            fitted.max(screen_pixels_per_pixel.max(1) as f32 / screen_pixels)
+        })
+}
+
+/// Returns the factor by which the canvas of a target has to be scaled for its main camera.
+/// This is only ever not 1 for [`PixelDensity::PixelPerfect`], where the canvas grows so that its texels line up with screen pixels.
+pub(crate) fn get_canvas_scale(
+    pixelate: &Pixelate,
+    bounds: &Aabb,
+    target_transform: &GlobalTransform,
+    camera: &Camera,
+    projection: &Projection,
+    camera_transform: &GlobalTransform,
+) -> f32 {
+    let PixelDensity::PixelPerfect {
+        screen_pixels_per_pixel,
+    } = pixelate.density
+    else {
+        return 1.;
+    };
+    let distance = camera_transform
+        .translation()
+        .distance(get_world_center(bounds, target_transform));
+    get_pixel_perfect_layout(
+        screen_pixels_per_pixel,
+        get_max_radius(bounds),
+        get_world_scale(target_transform),
+        distance,
+        camera,
+        projection,
+    )
+    .map_or(1., |(_, canvas_scale)| canvas_scale)
+}
+
+/// Moves the canvas at `translation` so that its edges lie on the main camera's physical pixel grid.
+/// `size` is the canvas size in screen pixels.
+pub(crate) fn snap_to_pixel_grid(
+    translation: Vec3,
+    size: UVec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    let viewport_size = camera.physical_viewport_size()?.as_vec2();
+    let ndc = camera.world_to_ndc(camera_transform, translation)?;
+    let half_size = size.as_vec2() / 2.;
+    let center = (ndc.truncate() * 0.5 + 0.5) * viewport_size;
+    let center = (center - half_size).round() + half_size;
+    let ndc = ((center / viewport_size - 0.5) * 2.).extend(ndc.z);
+    camera.ndc_to_world(camera_transform, ndc)
+}
+
+/// Returns the texture size and canvas scale that make every texel cover exactly
+/// `screen_pixels_per_pixel` × `screen_pixels_per_pixel` physical pixels.
+///
+/// The canvas sits `radius * scale` in front of the target, facing the camera, and is `2 * radius * scale` wide before scaling.
+fn get_pixel_perfect_layout(
+    screen_pixels_per_pixel: u32,
+    radius: f32,
+    scale: f32,
+    distance: f32,
+    camera: &Camera,
+    projection: &Projection,
+) -> Option<(u32, f32)> {
+    let screen_pixels_per_pixel = screen_pixels_per_pixel.max(1);
+    let canvas_distance = distance - radius * scale;
+    let screen_pixels = get_screen_size(radius * scale * 2., canvas_distance, camera, projection)?;
+    if screen_pixels <= 0. {
+        return None;
+    }
+    let size = to_bucket(screen_pixels / screen_pixels_per_pixel as f32);
+    let canvas_scale = (size * screen_pixels_per_pixel) as f32 / screen_pixels;
+    Some((size, canvas_scale))
+}
+
+/// Returns how many physical pixels of the main camera's viewport a length of `size` at `distance` covers.
+pub(crate) fn get_screen_size(
+    size: f32,
+    distance: f32,
+    camera: &Camera,
+    projection: &Projection,
+) -> Option<f32> {
+    let viewport_height = camera.physical_viewport_size()?.y as f32;
+    let visible_height = match projection {
+        Projection::Perspective(perspective) => {
+            2. * distance.max(f32::EPSILON) * (perspective.fov / 2.).tan()
+        }
+        Projection::Orthographic(orthographic) => orthographic.area.height(),
+        Projection::Custom(_) => return None,
+    };
+    Some(size * viewport_height / visible_height)
+}
+
+fn to_bucket(pixels: f32) -> u32 {
+    let pixels = (pixels.ceil() as u32).clamp(1, MAX_SIZE);
+    pixels.div_ceil(SIZE_BUCKET) * SIZE_BUCKET
+}
+
+fn to_bucket_size(pixels: Vec2) -> UVec2 {
+    UVec2::new(to_bucket(pixels.x), to_bucket(pixels.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::math::Vec3A;
+    use bevy::render::camera::{camera_system, ManualTextureViews, Viewport};
+    use bevy::window::{
+        PrimaryWindow, WindowCreated, WindowResized, WindowResolution, WindowScaleFactorChanged,
+    };
+
+    /// Bounds with a diameter of 2.
+    fn bounds() -> Aabb {
+        Aabb {
+            center: Vec3A::ZERO,
+            half_extents: Vec3A::X,
+        }
+    }
+
+    /// A camera looking down -Z with a 1000 × 800 viewport and an up-to-date projection.
+    fn main_camera(distance: f32) -> (Camera, Projection, GlobalTransform) {
+        let mut world = World::new();
+        world.init_resource::<Events<WindowResized>>();
+        world.init_resource::<Events<WindowCreated>>();
+        world.init_resource::<Events<WindowScaleFactorChanged>>();
+        world.init_resource::<Events<AssetEvent<Image>>>();
+        world.init_resource::<Assets<Image>>();
+        world.init_resource::<ManualTextureViews>();
+        world.spawn((
+            Window {
+                resolution: WindowResolution::new(1000., 800.),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+        let camera = world
+            .spawn((
+                Camera {
+                    viewport: Some(Viewport {
+                        physical_size: UVec2::new(1000, 800),
+                        ..default()
+                    }),
+                    ..default()
+                },
+                Projection::default(),
+            ))
+            .id();
+        world.run_system_once(camera_system).unwrap();
+        let mut camera = world.entity_mut(camera);
+        let projection = camera.take::<Projection>().unwrap();
+        let camera = camera.take::<Camera>().unwrap();
+        let transform = GlobalTransform::from_xyz(0., 0., distance);
+        (camera, projection, transform)
+    }
+
+    fn size(
+        pixelate: &Pixelate,
+        main_camera: Option<&(Camera, Projection, GlobalTransform)>,
+    ) -> UVec2 {
+        canvas_image_size(
+            pixelate,
+            &bounds(),
+            &GlobalTransform::IDENTITY,
+            main_camera.map(|(camera, projection, transform)| (camera, projection, transform)),
+            None,
+        )
+    }
+
+    #[test]
+    fn fixed_density_uses_the_pixelate_size() {
+        let pixelate = Pixelate {
+            horizontal_pixels: 30,
+            vertical_pixels: 20,
+            ..default()
+        };
+        assert_eq!(size(&pixelate, None), UVec2::new(30, 20));
+    }
+
+    #[test]
+    fn world_space_sizes_are_rounded_up_to_the_bucket() {
+        assert_eq!(size(&Pixelate::world_space(12.), None), UVec2::splat(24));
+        assert_eq!(size(&Pixelate::world_space(10.), None), UVec2::splat(24));
+        assert_eq!(size(&Pixelate::world_space(12.1), None), UVec2::splat(32));
+    }
+
+    #[test]
+    fn derived_sizes_are_capped() {
+        assert_eq!(
+            size(&Pixelate::world_space(1e6), None),
+            UVec2::splat(MAX_SIZE)
+        );
+        assert_eq!(
+            size(&Pixelate::world_space(0.), None),
+            UVec2::splat(SIZE_BUCKET)
+        );
+    }
+
+    #[test]
+    fn screen_space_sizes_follow_the_distance() {
+        // The diameter covers `2 * 800 / (2 * 10 * tan(PI / 8))` ≈ 193 screen pixels at a distance of 10.
+        let pixelate = Pixelate::screen_space(4.);
+        assert_eq!(size(&pixelate, Some(&main_camera(10.))), UVec2::splat(56));
+        assert_eq!(size(&pixelate, Some(&main_camera(20.))), UVec2::splat(32));
+    }
+
+    #[test]
+    fn screen_space_without_main_camera_uses_the_pixelate_size() {
+        let pixelate = Pixelate::screen_space(4.);
+        assert_eq!(size(&pixelate, None), UVec2::splat(64));
+    }
+
+    #[test]
+    fn pixel_perfect_texels_cover_whole_screen_pixels() {
+        let pixelate = Pixelate::pixel_perfect(3);
+        for distance in [10., 13.7, 40.] {
+            let main_camera = main_camera(distance);
+            let (camera, projection, camera_transform) = &main_camera;
+            let size = size(&pixelate, Some(&main_camera));
+            assert_eq!(size.x % SIZE_BUCKET, 0);
+            let scale = get_canvas_scale(
+                &pixelate,
+                &bounds(),
+                &GlobalTransform::IDENTITY,
+                camera,
+                projection,
+                camera_transform,
+            );
+            assert!(scale >= 1.);
+            // The canvas sits one radius in front of the target.
+            let screen_pixels =
+                get_screen_size(2. * scale, distance - 1., camera, projection).unwrap();
+            assert!((screen_pixels - (size.x * 3) as f32).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn only_pixel_perfect_density_scales_the_canvas() {
+        let (camera, projection, camera_transform) = main_camera(10.);
+        let scale = get_canvas_scale(
+            &Pixelate::screen_space(3.),
+            &bounds(),
+            &GlobalTransform::IDENTITY,
+            &camera,
+            &projection,
+            &camera_transform,
+        );
+        assert_eq!(scale, 1.);
+    }
+
+    #[test]
+    fn snapping_puts_canvas_edges_on_the_pixel_grid() {
+        let (camera, _, camera_transform) = main_camera(10.);
+        let to_pixels = |translation| {
+            let ndc = camera.world_to_ndc(&camera_transform, translation).unwrap();
+            ((ndc.truncate() * 0.5 + 0.5) * Vec2::new(1000., 800.), ndc.z)
+        };
+        let translation = Vec3::new(0.0123, -0.0456, 0.3);
+        let (center, depth) = to_pixels(translation);
+        for size in [UVec2::splat(16), UVec2::new(16, 17)] {
+            let snapped =
+                snap_to_pixel_grid(translation, size, &camera, &camera_transform).unwrap();
+            let (snapped_center, snapped_depth) = to_pixels(snapped);
+            let edge = snapped_center - size.as_vec2() / 2.;
+            assert!((edge - edge.round()).abs().max_element() < 1e-2);
+            assert!((snapped_center - center).abs().max_element() <= 0.5 + 1e-2);
+            assert!((snapped_depth - depth).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn tight_frames_are_sized_per_axis() {
+        let size = canvas_image_size(
+            &Pixelate::world_space(12.),
+            &bounds(),
+            &GlobalTransform::IDENTITY,
+            None,
+            Some(Vec2::new(2., 1.)),
+        );
+        assert_eq!(size, UVec2::new(24, 16));
+    }
+
+    #[test]
+    fn tight_texels_are_square_and_cover_the_frame() {
+        let (camera, projection, camera_transform) = main_camera(10.);
+        let frame = Vec2::new(2., 1.);
+        let image_size = UVec2::new(24, 16);
+        let texel_size = get_tight_texel_size(
+            &Pixelate::world_space(12.),
+            &bounds(),
+            frame,
+            image_size,
+            &GlobalTransform::IDENTITY,
+            &camera,
+            &projection,
+            &camera_transform,
+        );
+        let canvas_size = image_size.as_vec2() * texel_size;
+        assert!(canvas_size.cmpge(frame - 1e-5).all(), "{canvas_size}");
+        assert!((canvas_size.x - frame.x).abs() < 1e-5, "{canvas_size}");
+    }
+}
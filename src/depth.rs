@@ -0,0 +1,189 @@
+use crate::{CanvasAlphaMode, PixelationCamera};
+use bevy::asset::embedded_asset;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::ecs::query::QueryItem;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::{
+    camera::CameraProjection,
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_asset::RenderAssets,
+    render_graph::{
+        NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+    },
+    render_resource::{
+        AsBindGroup, Extent3d, ShaderRef, TextureDescriptor, TextureDimension, TextureFormat,
+        TextureUsages,
+    },
+    renderer::RenderContext,
+    texture::GpuImage,
+    view::ViewDepthTexture,
+    RenderApp,
+};
+
+/// Sets up everything needed for [`Pixelate::depth_compositing`](crate::Pixelate::depth_compositing).
+/// Shared by all instances of [`PixelateMeshPlugin`](crate::PixelateMeshPlugin).
+pub(crate) struct CanvasDepthPlugin;
+
+impl Plugin for CanvasDepthPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/canvas_depth.wgsl");
+        app.add_plugins((
+            MaterialPlugin::<CanvasDepthMaterial> {
+                prepass_enabled: false,
+                shadows_enabled: false,
+                ..default()
+            },
+            ExtractComponentPlugin::<PixelationDepth>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<CopyDepthNode>>(Core3d, CopyDepthLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    CopyDepthLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+}
+
+/// Placed on pixelation cameras of targets with depth compositing.
+/// After the main pass, the camera's depth is copied into `texture`, which `material` reads.
+#[derive(Debug, Component, ExtractComponent, Clone)]
+pub(crate) struct PixelationDepth {
+    pub(crate) texture: Handle<Image>,
+    pub(crate) material: Handle<CanvasDepthMaterial>,
+}
+
+/// Shows the image of a pixelation camera on a canvas and writes the depth of every texel,
+/// reconstructed from the pixelation camera's depth and projected into the main camera.
+#[derive(Debug, Asset, TypePath, AsBindGroup, Clone)]
+pub(crate) struct CanvasDepthMaterial {
+    #[uniform(0)]
+    pub(crate) world_from_pixelation_clip: Mat4,
+    #[texture(1)]
+    #[sampler(2)]
+    pub(crate) color_texture: Handle<Image>,
+    #[texture(3, sample_type = "depth")]
+    pub(crate) depth_texture: Handle<Image>,
+    /// Texels with a lower alpha are discarded.
+    #[uniform(4)]
+    pub(crate) alpha_cutoff: f32,
+    pub(crate) alpha_mode: AlphaMode,
+}
+
+impl CanvasDepthMaterial {
+    pub(crate) fn new(
+        color_texture: Handle<Image>,
+        depth_texture: Handle<Image>,
+        alpha_mode: CanvasAlphaMode,
+    ) -> Self {
+        // Writing depth only makes sense for texels that are either fully there or not at all, so blending is not an option.
+        let (alpha_mode, alpha_cutoff) = match alpha_mode {
+            CanvasAlphaMode::Blend => (AlphaMode::Mask(0.5), 0.5),
+            CanvasAlphaMode::Mask(threshold) => (AlphaMode::Mask(threshold), threshold),
+            // Empty texels have no depth, so they still need to be discarded.
+            CanvasAlphaMode::AlphaToCoverage => (AlphaMode::AlphaToCoverage, f32::EPSILON),
+        };
+        Self {
+            world_from_pixelation_clip: Mat4::IDENTITY,
+            color_texture,
+            depth_texture,
+            alpha_cutoff,
+            alpha_mode,
+        }
+    }
+}
+
+impl Material for CanvasDepthMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://pixelate_mesh/shaders/canvas_depth.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+}
+
+/// The format of the textures that the depth of pixelation cameras is copied into.
+pub(crate) const DEPTH_IMAGE_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+pub(crate) fn create_depth_image(size: UVec2) -> Image {
+    Image {
+        data: None,
+        texture_descriptor: TextureDescriptor {
+            label: Some("Pixelation depth texture"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                ..default()
+            },
+            dimension: TextureDimension::D2,
+            format: DEPTH_IMAGE_FORMAT,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        sampler: ImageSampler::nearest(),
+        ..default()
+    }
+}
+
+/// Keeps the depth materials in sync with the pixelation cameras they were rendered by.
+pub(crate) fn update_depth_materials(
+    pixelation_camera_query: Query<
+        (&Transform, &Projection, &PixelationDepth),
+        With<PixelationCamera>,
+    >,
+    mut materials: ResMut<Assets<CanvasDepthMaterial>>,
+) {
+    for (transform, projection, depth) in &pixelation_camera_query {
+        let Some(material) = materials.get_mut(&depth.material) else {
+            continue;
+        };
+        let clip_from_world =
+            projection.get_clip_from_view() * transform.compute_matrix().inverse();
+        material.world_from_pixelation_clip = clip_from_world.inverse();
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct CopyDepthLabel;
+
+/// Copies the depth of pixelation cameras with a [`PixelationDepth`] into its texture.
+#[derive(Default)]
+struct CopyDepthNode;
+
+impl ViewNode for CopyDepthNode {
+    type ViewQuery = (&'static ViewDepthTexture, &'static PixelationDepth);
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_depth, depth): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let images = world.resource::<RenderAssets<GpuImage>>();
+        let Some(image) = images.get(&depth.texture) else {
+            return Ok(());
+        };
+        // Right after a resize, the view's depth texture may not match the new image yet.
+        if view_depth.texture.size() != image.size {
+            return Ok(());
+        }
+        render_context.command_encoder().copy_texture_to_texture(
+            view_depth.texture.as_image_copy(),
+            image.texture.as_image_copy(),
+            image.size,
+        );
+        Ok(())
+    }
+}
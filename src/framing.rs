@@ -0,0 +1,447 @@
+use crate::util::{aabb_corners, get_world_center};
+use crate::PixelationProjection;
+use bevy::math::Vec3A;
+use bevy::prelude::*;
+use bevy::render::camera::{CameraProjection, ScalingMode, SubCameraView};
+use bevy::render::primitives::Aabb;
+
+/// Where the pixelation camera is placed and what it sees.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Framing {
+    /// The distance between the pixelation camera and the center of the target.
+    pub(crate) distance: f32,
+    /// The height of the area the pixelation camera sees at the center of the target.
+    pub(crate) frame_height: f32,
+}
+
+/// Updates the pixelation camera's projection so that a sphere with the given radius exactly fills the render target,
+/// and returns where the camera has to be placed for that.
+pub(crate) fn frame_sphere(
+    pixelation_projection: PixelationProjection,
+    radius: f32,
+    aspect_ratio: f32,
+    projection: &mut Projection,
+) -> Framing {
+    let radius = radius.max(f32::EPSILON);
+    match pixelation_projection {
+        PixelationProjection::Perspective { fov } => {
+            // The sphere touches the frustum where it is narrowest.
+            let vertical_half_angle = fov / 2.;
+            let horizontal_half_angle = (vertical_half_angle.tan() * aspect_ratio).atan();
+            let half_angle = vertical_half_angle.min(horizontal_half_angle);
+            let distance = radius / half_angle.sin();
+            let far = distance + radius;
+            match projection {
+                Projection::Perspective(perspective) => {
+                    perspective.fov = fov;
+                    perspective.far = far;
+                }
+                _ => {
+                    *projection = Projection::Perspective(PerspectiveProjection {
+                        fov,
+                        far,
+                        ..default()
+                    });
+                }
+            }
+            Framing {
+                distance,
+                frame_height: 2. * distance * vertical_half_angle.tan(),
+            }
+        }
+        // Framed through the canvas by `frame_window` instead.
+        PixelationProjection::OffAxis => Framing {
+            distance: radius,
+            frame_height: radius * 2.,
+        },
+        PixelationProjection::Orthographic => {
+            // Any distance works as long as the whole sphere is in front of the camera.
+            let distance = radius * 2.;
+            let scaling_mode = ScalingMode::AutoMin {
+                min_width: radius * 2.,
+                min_height: radius * 2.,
+            };
+            let far = distance + radius;
+            match projection {
+                Projection::Orthographic(orthographic) => {
+                    orthographic.scaling_mode = scaling_mode;
+                    orthographic.far = far;
+                }
+                _ => {
+                    *projection = Projection::Orthographic(OrthographicProjection {
+                        scaling_mode,
+                        far,
+                        ..OrthographicProjection::default_3d()
+                    });
+                }
+            }
+            let frame_height = if aspect_ratio < 1. {
+                radius * 2. / aspect_ratio
+            } else {
+                radius * 2.
+            };
+            Framing {
+                distance,
+                frame_height,
+            }
+        }
+    }
+}
+
+/// Returns the size of the smallest rectangle centered on the target that contains all of `bounds`
+/// as seen by the pixelation camera at `camera_transform`, which looks at the center of `bounds`.
+/// For perspective projections, the rectangle lies in the plane through the center of `bounds`.
+pub(crate) fn get_tight_frame(
+    pixelation_projection: PixelationProjection,
+    bounds: &Aabb,
+    target_transform: &GlobalTransform,
+    camera_transform: &Transform,
+) -> Vec2 {
+    let view_from_world = camera_transform.compute_affine().inverse();
+    let view_from_local = view_from_world * target_transform.affine();
+    let target_depth = -view_from_world
+        .transform_point3(get_world_center(bounds, target_transform))
+        .z;
+    let half_size = aabb_corners(bounds)
+        .map(|corner| {
+            let corner = view_from_local.transform_point3(corner);
+            match pixelation_projection {
+                PixelationProjection::Orthographic => corner.truncate(),
+                // Project the corner onto the plane through the center.
+                _ => corner.truncate() * target_depth / (-corner.z).max(f32::EPSILON),
+            }
+        })
+        .fold(Vec2::ZERO, |half_size, corner| half_size.max(corner.abs()));
+    half_size * 2.
+}
+
+/// Updates the projection set up by [`frame_sphere`] so that the camera sees exactly a rectangle of `size` at `distance`.
+/// `size` needs to have the aspect ratio of the render target, or the image is stretched.
+pub(crate) fn frame_rectangle(distance: f32, size: Vec2, projection: &mut Projection) -> Framing {
+    let size = size.max(Vec2::splat(f32::EPSILON));
+    match projection {
+        Projection::Perspective(perspective) => {
+            perspective.fov = 2. * (size.y / 2. / distance).atan();
+        }
+        Projection::Orthographic(orthographic) => {
+            orthographic.scaling_mode = ScalingMode::Fixed {
+                width: size.x,
+                height: size.y,
+            };
+        }
+        Projection::Custom(_) => {}
+    }
+    Framing {
+        distance,
+        frame_height: size.y,
+    }
+}
+
+/// Places the pixelation camera at `eye` and updates its projection so that the render target shows exactly
+/// what `eye` would see through the canvas, a rectangle centered at `center` with the given axes and half size.
+pub(crate) fn frame_window(
+    eye: Vec3,
+    center: Vec3,
+    right: Vec3,
+    up: Vec3,
+    half_size: Vec2,
+    transform: &mut Transform,
+    projection: &mut Projection,
+) {
+    let normal = right.cross(up);
+    transform.translation = eye;
+    transform.rotation = Quat::from_mat3(&Mat3::from_cols(right, up, normal));
+
+    let to_center = center - eye;
+    let near = (-to_center.dot(normal)).max(f32::EPSILON);
+    let offset = Vec2::new(to_center.dot(right), to_center.dot(up));
+    let off_axis = OffAxisProjection {
+        min: offset - half_size,
+        max: offset + half_size,
+        near,
+        far: near + half_size.max_element() * 4.,
+    };
+    match projection {
+        Projection::Custom(custom) if custom.get::<OffAxisProjection>().is_some() => {
+            if let Some(projection) = custom.get_mut::<OffAxisProjection>() {
+                *projection = off_axis;
+            }
+        }
+        _ => *projection = Projection::custom(off_axis),
+    }
+}
+
+/// A perspective projection whose near plane is an arbitrary rectangle, not necessarily centered on the view axis.
+#[derive(Debug, Copy, Clone)]
+struct OffAxisProjection {
+    /// The bottom left corner of the near plane in view space.
+    min: Vec2,
+    /// The top right corner of the near plane in view space.
+    max: Vec2,
+    near: f32,
+    far: f32,
+}
+
+impl OffAxisProjection {
+    fn get_clip_from_view_for(&self, min: Vec2, max: Vec2) -> Mat4 {
+        let size = max - min;
+        let scale = 2. * self.near / size;
+        let shift = (max + min) / size;
+        // Reverse-z with an infinite far plane, like Bevy's `PerspectiveProjection`.
+        Mat4::from_cols(
+            Vec4::new(scale.x, 0., 0., 0.),
+            Vec4::new(0., scale.y, 0., 0.),
+            Vec4::new(shift.x, shift.y, 0., -1.),
+            Vec4::new(0., 0., self.near, 0.),
+        )
+    }
+}
+
+impl CameraProjection for OffAxisProjection {
+    fn get_clip_from_view(&self) -> Mat4 {
+        self.get_clip_from_view_for(self.min, self.max)
+    }
+
+    fn get_clip_from_view_for_sub(&self, sub_view: &SubCameraView) -> Mat4 {
+        let full_size = sub_view.full_size.as_vec2();
+        let size = self.max - self.min;
+        // The sub view's offset is measured from the top left.
+        let offset = Vec2::new(
+            sub_view.offset.x,
+            full_size.y - sub_view.offset.y - sub_view.size.y as f32,
+        );
+        let min = self.min + size * offset / full_size;
+        let max = min + size * sub_view.size.as_vec2() / full_size;
+        self.get_clip_from_view_for(min, max)
+    }
+
+    fn update(&mut self, _width: f32, _height: f32) {}
+
+    fn far(&self) -> f32 {
+        self.far
+    }
+
+    fn get_frustum_corners(&self, z_near: f32, z_far: f32) -> [Vec3A; 8] {
+        let corners = |z: f32| {
+            let min = self.min * z.abs() / self.near;
+            let max = self.max * z.abs() / self.near;
+            [
+                Vec3A::new(max.x, min.y, z), // bottom right
+                Vec3A::new(max.x, max.y, z), // top right
+                Vec3A::new(min.x, max.y, z), // top left
+                Vec3A::new(min.x, min.y, z), // bottom left
+            ]
+        };
+        let [a, b, c, d] = corners(z_near);
+        let [e, f, g, h] = corners(z_far);
+        [a, b, c, d, e, f, g, h]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::primitives::Frustum;
+    use std::f32::consts::FRAC_PI_4;
+
+    /// Frames a sphere and returns the distances from its center to the left, right, bottom and top planes of the frustum.
+    fn side_distances(
+        pixelation_projection: PixelationProjection,
+        radius: f32,
+        aspect_ratio: f32,
+    ) -> (Framing, [f32; 4]) {
+        let mut projection = Projection::default();
+        let framing = frame_sphere(pixelation_projection, radius, aspect_ratio, &mut projection);
+        projection.update(100. * aspect_ratio, 100.);
+        let frustum = Frustum::from_clip_from_world(&projection.get_clip_from_view());
+        let center = Vec3::new(0., 0., -framing.distance).extend(1.);
+        let distances = [0, 1, 2, 3].map(|i| frustum.half_spaces[i].normal_d().dot(center));
+        (framing, distances)
+    }
+
+    fn assert_touches(distances: [f32; 4], radius: f32) {
+        let closest = distances.into_iter().fold(f32::INFINITY, f32::min);
+        assert!((closest - radius).abs() < 1e-4, "{distances:?}");
+    }
+
+    #[test]
+    fn perspective_frustum_touches_the_sphere() {
+        for aspect_ratio in [0.5, 1., 2.] {
+            let (_, distances) = side_distances(
+                PixelationProjection::Perspective { fov: FRAC_PI_4 },
+                1.5,
+                aspect_ratio,
+            );
+            assert_touches(distances, 1.5);
+        }
+    }
+
+    #[test]
+    fn perspective_frame_height_matches_the_fov() {
+        let (framing, _) = side_distances(
+            PixelationProjection::Perspective { fov: FRAC_PI_4 },
+            1.5,
+            1.,
+        );
+        assert!(
+            (framing.frame_height / 2. - framing.distance * (FRAC_PI_4 / 2.).tan()).abs() < 1e-5
+        );
+    }
+
+    #[test]
+    fn orthographic_frustum_touches_the_sphere() {
+        for aspect_ratio in [0.5, 1., 2.] {
+            let (framing, distances) =
+                side_distances(PixelationProjection::Orthographic, 1.5, aspect_ratio);
+            assert_touches(distances, 1.5);
+            // The frame height is what the canvas is sized against, so it has to be the actual visible height.
+            let [.., bottom, top] = distances;
+            assert!((bottom - framing.frame_height / 2.).abs() < 1e-4);
+            assert!((top - framing.frame_height / 2.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn the_whole_sphere_is_in_front_of_the_camera() {
+        for pixelation_projection in [
+            PixelationProjection::Perspective { fov: FRAC_PI_4 },
+            PixelationProjection::Orthographic,
+        ] {
+            let mut projection = Projection::default();
+            let framing = frame_sphere(pixelation_projection, 1.5, 1., &mut projection);
+            assert!(framing.distance > 1.5);
+            assert!(projection.far() >= framing.distance + 1.5);
+        }
+    }
+
+    fn window() -> OffAxisProjection {
+        OffAxisProjection {
+            min: Vec2::new(-0.7, -0.3),
+            max: Vec2::new(1.3, 1.2),
+            near: 2.,
+            far: 10.,
+        }
+    }
+
+    #[test]
+    fn off_axis_window_corners_map_to_the_ndc_corners() {
+        let window = window();
+        let clip_from_view = window.get_clip_from_view();
+        for (x, ndc_x) in [(window.min.x, -1.), (window.max.x, 1.)] {
+            for (y, ndc_y) in [(window.min.y, -1.), (window.max.y, 1.)] {
+                let ndc = clip_from_view.project_point3(Vec3::new(x, y, -window.near));
+                // Reverse-z: the near plane has a depth of 1.
+                assert!(ndc.abs_diff_eq(Vec3::new(ndc_x, ndc_y, 1.), 1e-5), "{ndc}");
+            }
+        }
+    }
+
+    #[test]
+    fn off_axis_depth_decreases_with_distance() {
+        let clip_from_view = window().get_clip_from_view();
+        let near = clip_from_view.project_point3(Vec3::new(0., 0., -3.)).z;
+        let far = clip_from_view.project_point3(Vec3::new(0., 0., -30.)).z;
+        assert!(1. > near && near > far && far > 0.);
+    }
+
+    #[test]
+    fn off_axis_sub_view_covers_its_part_of_the_window() {
+        let window = window();
+        // The top left quarter.
+        let clip_from_view = window.get_clip_from_view_for_sub(&SubCameraView {
+            full_size: UVec2::new(100, 100),
+            offset: Vec2::ZERO,
+            size: UVec2::new(50, 50),
+        });
+        let center = (window.min + window.max) / 2.;
+        let top_left =
+            clip_from_view.project_point3(Vec3::new(window.min.x, window.max.y, -window.near));
+        let bottom_right = clip_from_view.project_point3(center.extend(-window.near));
+        assert!(
+            top_left.abs_diff_eq(Vec3::new(-1., 1., 1.), 1e-5),
+            "{top_left}"
+        );
+        assert!(
+            bottom_right.abs_diff_eq(Vec3::new(1., -1., 1.), 1e-5),
+            "{bottom_right}"
+        );
+    }
+
+    #[test]
+    fn framed_window_corners_fill_the_render_target() {
+        let mut transform = Transform::default();
+        let mut projection = Projection::default();
+        let center = Vec3::new(0., 0., 1.);
+        let half_size = Vec2::new(1., 0.75);
+        frame_window(
+            Vec3::new(0.5, 1., 5.),
+            center,
+            Vec3::X,
+            Vec3::Y,
+            half_size,
+            &mut transform,
+            &mut projection,
+        );
+        let clip_from_world =
+            projection.get_clip_from_view() * transform.compute_matrix().inverse();
+        for x in [-1., 1.] {
+            for y in [-1., 1.] {
+                let corner = center + Vec3::new(x * half_size.x, y * half_size.y, 0.);
+                let ndc = clip_from_world.project_point3(corner);
+                assert!(ndc.abs_diff_eq(Vec3::new(x, y, 1.), 1e-5), "{ndc}");
+            }
+        }
+    }
+
+    #[test]
+    fn tight_frame_contains_the_projected_box() {
+        let bounds = Aabb {
+            center: Vec3A::ZERO,
+            half_extents: Vec3A::new(1., 0.5, 0.25),
+        };
+        let camera_transform = Transform::from_xyz(0., 0., 5.);
+        let orthographic = get_tight_frame(
+            PixelationProjection::Orthographic,
+            &bounds,
+            &GlobalTransform::IDENTITY,
+            &camera_transform,
+        );
+        assert!(
+            orthographic.abs_diff_eq(Vec2::new(2., 1.), 1e-5),
+            "{orthographic}"
+        );
+
+        // The front face is closer to the camera, so it covers more of the plane through the center.
+        let perspective = get_tight_frame(
+            PixelationProjection::Perspective { fov: FRAC_PI_4 },
+            &bounds,
+            &GlobalTransform::IDENTITY,
+            &camera_transform,
+        );
+        let expected = Vec2::new(2., 1.) * 5. / 4.75;
+        assert!(perspective.abs_diff_eq(expected, 1e-5), "{perspective}");
+    }
+
+    #[test]
+    fn framed_rectangle_fills_the_render_target() {
+        let size = Vec2::new(3., 2.);
+        for pixelation_projection in [
+            PixelationProjection::Perspective { fov: FRAC_PI_4 },
+            PixelationProjection::Orthographic,
+        ] {
+            let mut projection = Projection::default();
+            let framing = frame_sphere(pixelation_projection, 1.5, 1.5, &mut projection);
+            let framing = frame_rectangle(framing.distance, size, &mut projection);
+            assert_eq!(framing.frame_height, size.y);
+            projection.update(300., 200.);
+            let clip_from_view = projection.get_clip_from_view();
+            for x in [-1., 1.] {
+                for y in [-1., 1.] {
+                    let corner = (Vec2::new(x, y) * size / 2.).extend(-framing.distance);
+                    let ndc = clip_from_view.project_point3(corner);
+                    assert!(ndc.truncate().abs_diff_eq(Vec2::new(x, y), 1e-4), "{ndc}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use bevy::core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
+use bevy::core_pipeline::prepass::ViewPrepassTextures;
+use bevy::ecs::query::QueryItem;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::{
+    extract_component::{ComponentUniforms, DynamicUniformIndex, UniformComponentPlugin},
+    render_graph::{NodeRunError, RenderGraphContext, ViewNode},
+    render_resource::{
+        binding_types::{texture_2d, uniform_buffer},
+        encase::internal::WriteInto,
+        BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindGroupLayoutEntryBuilder,
+        CachedRenderPipelineId, ColorTargetState, ColorWrites, FragmentState, IntoBinding,
+        MultisampleState, Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
+        RenderPassDescriptor, RenderPipelineDescriptor, ShaderDefVal, ShaderStages, ShaderType,
+        SpecializedRenderPipeline, SpecializedRenderPipelines, TextureFormat, TextureSampleType,
+        TextureView,
+    },
+    renderer::{RenderContext, RenderDevice},
+    view::{ExtractedView, ViewTarget},
+    Render, RenderApp, RenderSet,
+};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A fullscreen pass over the images rendered by pixelation cameras,
+/// enabled by placing the implementing component on a pixelation camera.
+///
+/// The shader binds the rendered image at binding 0, the [`FullscreenPass::Settings`] at binding 1
+/// and the [`FullscreenPass::textures`] from binding 2 on.
+pub(crate) trait FullscreenPass: Component {
+    /// Extracted alongside the component and uploaded once per frame.
+    type Settings: Component + ShaderType + WriteInto + Clone;
+    /// Selects the shader defs and the bind group layout of the pipeline.
+    type Key: Copy + Eq + Hash + Send + Sync + 'static;
+
+    /// Prefixes the labels of the pipeline, bind groups and render pass.
+    const LABEL: &'static str;
+    /// The embedded path of the fragment shader.
+    const SHADER: &'static str;
+
+    /// Every possible layout of the textures, see [`FullscreenPass::layout_index`].
+    fn texture_layouts() -> Vec<Vec<BindGroupLayoutEntryBuilder>>;
+
+    /// The index into [`FullscreenPass::texture_layouts`] used by a key.
+    fn layout_index(_key: Self::Key) -> usize {
+        0
+    }
+
+    fn shader_defs(key: Self::Key) -> Vec<ShaderDefVal>;
+
+    fn key(&self, msaa: &Msaa) -> Self::Key;
+
+    /// The textures bound after the settings, or `None` while any of them is not ready yet.
+    fn textures<'w>(
+        &'w self,
+        prepass: Option<&'w ViewPrepassTextures>,
+        world: &'w World,
+    ) -> Option<Vec<&'w TextureView>>;
+}
+
+/// Prepares the pipelines and settings of a [`FullscreenPass`].
+/// The pass still needs its `ExtractComponentPlugin` and its node in the render graph, see [`FullscreenNode`].
+pub(crate) struct FullscreenPassPlugin<P>(PhantomData<fn() -> P>);
+
+impl<P> Default for FullscreenPassPlugin<P> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<P: FullscreenPass> Plugin for FullscreenPassPlugin<P> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(UniformComponentPlugin::<P::Settings>::default());
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<FullscreenPipeline<P>>>()
+            .add_systems(
+                Render,
+                prepare_fullscreen_pipelines::<P>.in_set(RenderSet::Prepare),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<FullscreenPipeline<P>>();
+    }
+}
+
+#[derive(Resource)]
+struct FullscreenPipeline<P> {
+    /// Indexed by [`FullscreenPass::layout_index`].
+    layouts: Vec<BindGroupLayout>,
+    shader: Handle<Shader>,
+    marker: PhantomData<fn() -> P>,
+}
+
+impl<P: FullscreenPass> FromWorld for FullscreenPipeline<P> {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layouts = P::texture_layouts()
+            .into_iter()
+            .map(|textures| {
+                let entries: Vec<BindGroupLayoutEntry> = [
+                    // The rendered image
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    uniform_buffer::<P::Settings>(true),
+                ]
+                .into_iter()
+                .chain(textures)
+                .enumerate()
+                .map(|(binding, entry)| entry.build(binding as u32, ShaderStages::FRAGMENT))
+                .collect();
+                render_device
+                    .create_bind_group_layout(&*format!("{}_bind_group_layout", P::LABEL), &entries)
+            })
+            .collect();
+        let shader = world.load_asset(P::SHADER);
+        Self {
+            layouts,
+            shader,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FullscreenPipelineKey<K> {
+    format: TextureFormat,
+    pass: K,
+}
+
+impl<P: FullscreenPass> SpecializedRenderPipeline for FullscreenPipeline<P> {
+    type Key = FullscreenPipelineKey<P::Key>;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some(format!("{}_pipeline", P::LABEL).into()),
+            layout: vec![self.layouts[P::layout_index(key.pass)].clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs: P::shader_defs(key.pass),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: key.format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct FullscreenPipelineId<P: FullscreenPass> {
+    id: CachedRenderPipelineId,
+    key: P::Key,
+}
+
+fn prepare_fullscreen_pipelines<P: FullscreenPass>(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<FullscreenPipeline<P>>>,
+    fullscreen_pipeline: Res<FullscreenPipeline<P>>,
+    views: Query<(Entity, &ExtractedView, &Msaa, &P)>,
+) {
+    for (entity, view, msaa, pass) in &views {
+        let format = if view.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+        let key = pass.key(msaa);
+        let id = pipelines.specialize(
+            &pipeline_cache,
+            &fullscreen_pipeline,
+            FullscreenPipelineKey { format, pass: key },
+        );
+        commands
+            .entity(entity)
+            .insert(FullscreenPipelineId::<P> { id, key });
+    }
+}
+
+/// Runs a [`FullscreenPass`] on every view that has it.
+pub(crate) struct FullscreenNode<P>(PhantomData<fn() -> P>);
+
+impl<P> Default for FullscreenNode<P> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<P: FullscreenPass> ViewNode for FullscreenNode<P> {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static P,
+        &'static FullscreenPipelineId<P>,
+        &'static DynamicUniformIndex<P::Settings>,
+        Option<&'static ViewPrepassTextures>,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, pass, pipeline_id, settings_index, prepass_textures): QueryItem<
+            'w,
+            Self::ViewQuery,
+        >,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(pipeline_id.id)
+        else {
+            return Ok(());
+        };
+        let Some(settings) = world
+            .resource::<ComponentUniforms<P::Settings>>()
+            .uniforms()
+            .binding()
+        else {
+            return Ok(());
+        };
+        let Some(textures) = pass.textures(prepass_textures, world) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let entries: Vec<BindGroupEntry> = [post_process.source.into_binding(), settings]
+            .into_iter()
+            .chain(textures.into_iter().map(IntoBinding::into_binding))
+            .enumerate()
+            .map(|(binding, resource)| BindGroupEntry {
+                binding: binding as u32,
+                resource,
+            })
+            .collect();
+        let layout =
+            &world.resource::<FullscreenPipeline<P>>().layouts[P::layout_index(pipeline_id.key)];
+        let bind_group = render_context.render_device().create_bind_group(
+            &*format!("{}_bind_group", P::LABEL),
+            layout,
+            &entries,
+        );
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some(&format!("{}_pass", P::LABEL)),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
+
+/// The settings of each pass, laid out like the settings struct of its shader.
+// `ShaderType` checks the type of each field in a function that is never called.
+#[allow(dead_code)]
+mod settings {
+    use bevy::prelude::*;
+    use bevy::render::render_resource::ShaderType;
+
+    /// The number of bands, see `LightingRampSettings` in `lighting_ramp.wgsl`.
+    #[derive(Component, ShaderType, Clone, Copy)]
+    pub(crate) struct LightingRampSettings {
+        pub(crate) bands: f32,
+    }
+
+    /// The color, width and threshold of the outline, see `OutlineSettings` in `outline.wgsl`.
+    #[derive(Component, ShaderType, Clone, Copy)]
+    pub(crate) struct OutlineSettings {
+        pub(crate) color: Vec4,
+        pub(crate) width: f32,
+        pub(crate) threshold: f32,
+    }
+
+    /// The spread and levels of the dither, see `QuantizeSettings` in `quantize.wgsl`.
+    #[derive(Component, ShaderType, Clone, Copy)]
+    pub(crate) struct QuantizeSettings {
+        pub(crate) spread: f32,
+        pub(crate) levels: f32,
+    }
+}
+
+pub(crate) use settings::{LightingRampSettings, OutlineSettings, QuantizeSettings};
@@ -0,0 +1,140 @@
+use crate::{PixelationLayers, PixelationRenderLayers};
+use bevy::platform_support::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+/// Keeps track of the render layers handed out from [`PixelationLayers::first_allocated_layer`] onward:
+/// one per main camera to see its own canvases, and one per target if [`PixelationLayers::per_target`] is set.
+#[derive(Debug, Resource, Default)]
+pub(crate) struct AllocatedLayers {
+    by_entity: HashMap<Entity, usize>,
+    free: Vec<usize>,
+    next: usize,
+}
+
+impl AllocatedLayers {
+    pub(crate) fn get(&self, entity: Entity) -> Option<usize> {
+        self.by_entity.get(&entity).copied()
+    }
+
+    pub(crate) fn allocate(&mut self, entity: Entity, config: &PixelationLayers) -> usize {
+        let layer = self.free.pop().unwrap_or_else(|| {
+            let layer = config.first_allocated_layer + self.next;
+            self.next += 1;
+            layer
+        });
+        self.by_entity.insert(entity, layer);
+        layer
+    }
+
+    pub(crate) fn release(&mut self, entity: Entity) -> Option<usize> {
+        let layer = self.by_entity.remove(&entity)?;
+        self.free.push(layer);
+        Some(layer)
+    }
+
+    /// The layers that `target` and its pixelation cameras are rendered on, allocating a layer for it if needed.
+    pub(crate) fn target_layers(
+        &mut self,
+        target: Entity,
+        config: &PixelationLayers,
+    ) -> RenderLayers {
+        if !config.per_target {
+            return config.shared.clone();
+        }
+        let layer = match self.get(target) {
+            Some(layer) => layer,
+            None => self.allocate(target, config),
+        };
+        RenderLayers::layer(layer)
+    }
+}
+
+/// Placed on every entity whose render layers the plugin replaced when pixelating `target`,
+/// so they can be restored once the target is no longer pixelated.
+#[derive(Debug, Component, Clone)]
+pub(crate) struct ReplacedRenderLayers {
+    pub(crate) target: Entity,
+    pub(crate) original: Option<RenderLayers>,
+}
+
+/// Places `entity` on `render_layers`, remembering the layers it was on before.
+pub(crate) fn replace_render_layers(
+    commands: &mut Commands,
+    entity: Entity,
+    target: Entity,
+    original: Option<&RenderLayers>,
+    render_layers: RenderLayers,
+) {
+    commands.entity(entity).try_insert((
+        ReplacedRenderLayers {
+            target,
+            original: original.cloned(),
+        },
+        render_layers,
+    ));
+}
+
+/// Placed on lights by [`PixelationLayers::mirror_lights`], holding the layers the plugin added to them.
+#[derive(Debug, Component, Clone, PartialEq)]
+pub(crate) struct MirroredLight(RenderLayers);
+
+/// Adds the layers of all pixelated targets to every light that is not only on layers of the plugin,
+/// and takes them away again once they are no longer needed.
+pub(crate) fn mirror_lights(
+    mut commands: Commands,
+    config: Res<PixelationLayers>,
+    target_query: Query<&PixelationRenderLayers>,
+    light_query: Query<
+        (Entity, Option<&RenderLayers>, Option<&MirroredLight>),
+        Or<(With<DirectionalLight>, With<PointLight>, With<SpotLight>)>,
+    >,
+) {
+    let pixelation_layers = if config.mirror_lights {
+        target_query
+            .iter()
+            .fold(config.shared.clone(), |layers, target_layers| {
+                layers.union(target_layers)
+            })
+    } else {
+        RenderLayers::none()
+    };
+    for (light, render_layers, mirrored) in &light_query {
+        if !config.mirror_lights && mirrored.is_none() {
+            continue;
+        }
+        let render_layers = render_layers.cloned().unwrap_or_default();
+        // The layers the light would be on without the plugin.
+        let own_layers = match mirrored {
+            Some(mirrored) => difference(&render_layers, &mirrored.0),
+            None => render_layers.clone(),
+        };
+        let on_main_layers = own_layers.iter().any(|layer| {
+            !config.shared.intersects(&RenderLayers::layer(layer))
+                && layer < config.first_allocated_layer
+        });
+        let added = if on_main_layers {
+            difference(&pixelation_layers, &own_layers)
+        } else {
+            RenderLayers::none()
+        };
+
+        let mut light_commands = commands.entity(light);
+        let desired_layers = own_layers.union(&added);
+        if desired_layers != render_layers {
+            light_commands.insert(desired_layers);
+        }
+        if added.iter().next().is_none() {
+            if mirrored.is_some() {
+                light_commands.remove::<MirroredLight>();
+            }
+        } else if mirrored.map(|mirrored| &mirrored.0) != Some(&added) {
+            light_commands.insert(MirroredLight(added));
+        }
+    }
+}
+
+/// The layers in `layers` that are not in `other`.
+fn difference(layers: &RenderLayers, other: &RenderLayers) -> RenderLayers {
+    layers.symmetric_difference(&layers.intersection(other))
+}
@@ -10,6 +10,40 @@
 //! - Add the `Pixelate` component to any entity that you want to pixelate.
 //!
 //! The tracking component is needed because the plugin draws the textures on 2D canvases that need to rotate to always face the main camera.
+//! You can add the tracking component to multiple cameras, e.g. for split-screen. Every camera gets its own canvases,
+//! which are placed on a dedicated render layer that is added to the camera's `RenderLayers`.
+//!
+//! Pixelated objects are rendered on `PIXELATION_RENDER_LAYERS`, so lights need to be on these layers to affect them.
+//! Use `PixelateMeshPlugin::with_layers` to pick other layers, or to give every target a layer of its own
+//! so that a light can affect a single pixelated object. Set `PixelationLayers::mirror_lights` to have the plugin
+//! put all other lights on the layers of the pixelated objects for you.
+//!
+//! The cameras rendering pixelated objects copy the environment map, ambient light, tonemapping, exposure, fog
+//! and color grading of their main camera. Use `PixelateMeshPlugin::with_inherited_camera_settings` to choose which.
+//!
+//! The textures the pixelated objects are rendered to are recycled by the `RenderTargetPool` resource, so animating
+//! their size is cheap. Its `stats` tell how many textures are in use.
+//!
+//! By default, a canvas is a transparent quad in front of its target, so the target can pop in front of walls or vanish
+//! behind floors. Use `Pixelate::with_depth_compositing` to make the canvas write the depth of the rendered target instead,
+//! so that it intersects the rest of the world properly.
+//!
+//! Overlapping canvases are sorted as a whole, which can go wrong with many of them or other transparent geometry around.
+//! Use `Pixelate::with_alpha_mode(CanvasAlphaMode::Mask(0.5))` to draw them with the opaque geometry instead.
+//!
+//! Add a `PixelPaletteHandle` to a target to reduce its colors to a `PixelPalette`,
+//! which can be loaded from `.gpl`, `.hex` and `.palette.png` files. Add a `PixelDither` as well to hide the banding
+//! with an ordered or blue noise dither.
+//!
+//! Targets that are much taller than wide or the other way around waste most of a square texture.
+//! Use `Pixelate::with_framing(CanvasFraming::Tight)` to frame them tightly instead, which also makes non-square
+//! `Pixelate` sizes keep their pixels square.
+//!
+//! Add a `LightingRamp` to a target to remap its brightness to a few bands or a ramp texture, which gives it
+//! the stepped lighting of cel shading.
+//!
+//! Add a `PixelOutline` to a target to draw a pixel-art outline around its silhouette, optionally with inner lines
+//! where its depth or normals change.
 //!
 //! ## Example
 //! The following is an annotated minimal example.
@@ -58,72 +92,236 @@
 //! }
 //! ```
 use bevy::prelude::*;
-use bevy::render::view::RenderLayers;
+use bevy::render::primitives::Aabb;
+use bevy::render::view::{RenderLayers, VisibilitySystems};
+use bevy::transform::TransformSystem;
 
 /// Everything you need to get started
 pub mod prelude {
-    pub use crate::{Pixelate, PixelateMeshPlugin, PIXELATION_RENDER_LAYERS};
+    pub use crate::{
+        BoundsMode, CanvasAlphaMode, CanvasFraming, CanvasMaterial, DitherPattern,
+        InheritedCameraSettings, InnerEdges, LightingRamp, OutlinePlacement, PixelDensity,
+        PixelDither, PixelOutline, PixelPalette, PixelPaletteHandle, Pixelate, PixelateMeshPlugin,
+        PixelationBounds, PixelationError, PixelationErrorReason, PixelationLayers,
+        PixelationProjection, PixelationRenderLayers, RenderTargetPool, RenderTargetPoolStats,
+        PIXELATION_RENDER_LAYERS,
+    };
 }
 
+mod bounds;
 mod creation;
+mod density;
+mod depth;
+mod framing;
+mod fullscreen;
+mod layers;
+mod lighting;
+mod main_camera;
+mod outline;
+mod palette;
+mod pool;
+mod quantize;
 mod ready_checks;
 mod recursive_layering;
 mod runtime;
 mod shadow;
 mod util;
 
+pub use pool::{RenderTargetPool, RenderTargetPoolStats};
+
 /// The plugin type for this crate.
 /// The generic parameter `C` is the type of the component that tracks the main camera.
+/// The generic parameter `M` is the material of the canvases, see [`CanvasMaterial`].
+/// If you use anything but the default [`StandardMaterial`], you need to add its [`MaterialPlugin`] yourself.
+///
+/// The plugin can be added once per main camera component, e.g. to give each camera its own canvas material.
+/// All instances share their render layers, inherited camera settings and render passes,
+/// so only the configuration of the first instance is used.
 #[derive(Debug)]
-pub struct PixelateMeshPlugin<C: Component> {
+pub struct PixelateMeshPlugin<C: Component, M: CanvasMaterial = StandardMaterial> {
+    layers: PixelationLayers,
+    camera_settings: InheritedCameraSettings,
     _camera_type: std::marker::PhantomData<C>,
+    _material_type: std::marker::PhantomData<M>,
 }
 
-impl<C: Component> Default for PixelateMeshPlugin<C> {
+impl<C: Component, M: CanvasMaterial> Default for PixelateMeshPlugin<C, M> {
     fn default() -> Self {
         Self {
+            layers: PixelationLayers::default(),
+            camera_settings: InheritedCameraSettings::default(),
             _camera_type: std::marker::PhantomData,
+            _material_type: std::marker::PhantomData,
         }
     }
 }
 
-impl<C> Plugin for PixelateMeshPlugin<C>
+impl<C: Component, M: CanvasMaterial> PixelateMeshPlugin<C, M> {
+    /// Sets the render layers used by the plugin.
+    /// All instances of the plugin share these, so only the first instance's layers are used.
+    pub fn with_layers(mut self, layers: PixelationLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Sets which components pixelation cameras copy from their main camera.
+    /// All instances of the plugin share these, so only the first instance's settings are used.
+    pub fn with_inherited_camera_settings(mut self, settings: InheritedCameraSettings) -> Self {
+        self.camera_settings = settings;
+        self
+    }
+}
+
+impl<C, M> Plugin for PixelateMeshPlugin<C, M>
 where
     C: Component,
+    M: CanvasMaterial,
 {
     fn build(&self, app: &mut App) {
-        app.register_type::<Pixelate>()
-            .init_resource::<ready_checks::ToPixelate>()
-            .init_resource::<creation::Ordering>()
-            .init_resource::<shadow::SetSceneShadow>()
-            .add_event::<ready_checks::PixelationTargetReadyEvent>()
-            .add_systems(Startup, shadow::create_shadow_material)
-            .add_systems(
-                Update,
-                (
-                    ready_checks::get_ready_pixelation_targets,
-                    ready_checks::mark_for_pixelation,
-                    creation::add_pixelation,
-                    recursive_layering::recursively_set_layer,
-                    shadow::add_shadow_caster,
-                    shadow::set_scene_shadow,
-                    runtime::update_pixelation,
-                ),
+        if !app.is_plugin_added::<SharedPixelationPlugin>() {
+            app.add_plugins(SharedPixelationPlugin {
+                layers: self.layers.clone(),
+                camera_settings: self.camera_settings,
+            });
+        }
+        app.add_systems(
+            Update,
+            (
+                main_camera::spawn_missing_pixelation_pairs::<C, M>,
+                runtime::update_pixelation::<C, M>,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                runtime::sync_cameras::<C>,
+                runtime::sync_camera_settings::<C>,
+                runtime::position_canvas::<C>,
+                runtime::sync_off_axis_cameras::<C>,
+                main_camera::despawn_removed_main_cameras::<C>,
             )
-            .add_systems(
-                PostUpdate,
+                .chain()
+                .in_set(PixelationSystems::SyncCameras),
+        );
+    }
+}
+
+/// Everything that does not depend on the main camera component or the canvas material,
+/// added by the first instance of [`PixelateMeshPlugin`] and shared by all others.
+struct SharedPixelationPlugin {
+    layers: PixelationLayers,
+    camera_settings: InheritedCameraSettings,
+}
+
+/// Orders the systems of [`SharedPixelationPlugin`] around those of each [`PixelateMeshPlugin`] instance.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+enum PixelationSystems {
+    PrepareTargets,
+    SyncCameras,
+    Finish,
+}
+
+impl Plugin for SharedPixelationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            depth::CanvasDepthPlugin,
+            quantize::QuantizePlugin,
+            lighting::LightingRampPlugin,
+            outline::OutlinePlugin,
+        ))
+        .register_type::<Pixelate>()
+        .register_type::<PixelationBounds>()
+        .register_type::<PixelationRenderLayers>()
+        .register_type::<PixelationLayers>()
+        .register_type::<InheritedCameraSettings>()
+        .register_type::<PixelPaletteHandle>()
+        .register_type::<PixelDither>()
+        .register_type::<PixelOutline>()
+        .register_type::<LightingRamp>()
+        .add_event::<PixelationError>()
+        .init_resource::<ready_checks::ToPixelate>()
+        .init_resource::<creation::Ordering>()
+        .init_resource::<shadow::SetSceneShadow>()
+        .insert_resource(self.layers.clone())
+        .insert_resource(self.camera_settings)
+        .init_resource::<layers::AllocatedLayers>()
+        .init_resource::<pool::RenderTargetPool>()
+        .add_event::<ready_checks::PixelationTargetReadyEvent>()
+        .configure_sets(
+            PostUpdate,
+            (
+                PixelationSystems::PrepareTargets,
+                PixelationSystems::SyncCameras,
+                PixelationSystems::Finish,
+            )
+                .chain()
+                .after(TransformSystem::TransformPropagate)
+                .before(VisibilitySystems::UpdateFrusta)
+                .before(VisibilitySystems::CalculateBounds),
+        )
+        .add_systems(
+            Startup,
+            (shadow::create_shadow_material, creation::create_canvas_mesh),
+        )
+        .add_systems(
+            Update,
+            (
+                ready_checks::get_ready_pixelation_targets,
+                ready_checks::mark_for_pixelation,
+                creation::add_pixelation,
+                recursive_layering::recursively_set_layer,
+                shadow::add_shadow_caster,
+                shadow::set_scene_shadow,
+                palette::update_palette_images,
+                quantize::update_quantization.after(palette::update_palette_images),
+                outline::update_outlines,
+                lighting::update_lighting_ramps,
+                layers::mirror_lights,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                (runtime::despawn_dependent_types, bounds::update_bounds)
+                    .chain()
+                    .in_set(PixelationSystems::PrepareTargets),
                 (
-                    runtime::position_canvas::<C>,
-                    runtime::sync_cameras::<C>,
-                    runtime::despawn_dependent_types,
+                    runtime::propagate_pixelation_transforms,
+                    depth::update_depth_materials,
+                    pool::trim_render_target_pool,
                 )
-                    .chain(),
-            )
-            .add_systems(PostUpdate, runtime::set_visible);
+                    .chain()
+                    .in_set(PixelationSystems::Finish),
+                runtime::set_visible,
+            ),
+        );
+    }
+}
+
+/// A material that shows the image rendered for a pixelated target on its canvases.
+/// Implement this to use your own material, e.g. for rim lights, hit flashes or dissolve effects,
+/// and pass it as the second generic parameter of [`PixelateMeshPlugin`].
+///
+/// Whenever the render texture is swapped or the target's [`Pixelate`] changes, the existing material is updated
+/// with [`CanvasMaterial::set_canvas_image`].
+/// Targets with [`Pixelate::depth_compositing`] use a material of the plugin instead.
+pub trait CanvasMaterial: Material {
+    /// Creates the material for a canvas of `target` that shows `image`.
+    /// The image is rendered with a transparent background, so the material should respect [`Pixelate::alpha_mode`].
+    fn from_canvas_image(image: Handle<Image>, target: Entity, pixelate: &Pixelate) -> Self;
+
+    /// Makes the material show `image` instead, with the current [`Pixelate`] of `target`.
+    /// By default, the material is replaced with a new one from [`CanvasMaterial::from_canvas_image`];
+    /// override this to keep any other state of the material.
+    fn set_canvas_image(&mut self, image: Handle<Image>, target: Entity, pixelate: &Pixelate) {
+        *self = Self::from_canvas_image(image, target, pixelate);
     }
 }
 
 /// Marks the entity containing a mesh to be pixelated.
+/// Removing it again despawns the canvases and cameras of the entity and restores its render layers,
+/// so pixelation can be toggled at runtime.
+/// Both the entity and the main camera may be children of other entities, e.g. a sword held by a character.
 #[derive(Debug, Component, Reflect, Default, Copy, Clone)]
 #[reflect(Component)]
 pub struct Pixelate {
@@ -131,6 +329,24 @@ pub struct Pixelate {
     pub horizontal_pixels: u32,
     /// How many pixels tall the final pixelated image should be.
     pub vertical_pixels: u32,
+    /// How the bounds that determine the canvas size are computed.
+    pub bounds: BoundsMode,
+    /// How the size of the render texture is determined.
+    /// Unless this is [`PixelDensity::Fixed`], `horizontal_pixels` and `vertical_pixels` are only used as a fallback.
+    pub density: PixelDensity,
+    /// Whether to snap the pixelation camera to its texel grid and offset the canvas by the remainder.
+    /// This keeps pixels from crawling across the surface of moving targets.
+    pub texel_snapping: bool,
+    /// The projection of the camera that renders the target.
+    pub projection: PixelationProjection,
+    /// How much of the camera's view the target fills, which also determines the shape of the canvas.
+    pub framing: CanvasFraming,
+    /// Whether the canvas writes the depth of the rendered target instead of its own,
+    /// so that the target is correctly occluded by and occludes the rest of the world.
+    /// Pixels are then either fully opaque or fully transparent, and [`CanvasAlphaMode::Blend`] acts like `CanvasAlphaMode::Mask(0.5)`.
+    pub depth_compositing: bool,
+    /// How the canvas blends with what is behind it.
+    pub alpha_mode: CanvasAlphaMode,
 }
 
 impl Pixelate {
@@ -139,25 +355,546 @@ impl Pixelate {
         Self {
             horizontal_pixels: horizontal_and_vertical_pixels,
             vertical_pixels: horizontal_and_vertical_pixels,
+            ..default()
+        }
+    }
+
+    /// Creates a new `Pixelate` component that renders the given number of pixels per world unit,
+    /// so that targets of different sizes share the same pixel size.
+    pub fn world_space(pixels_per_unit: f32) -> Self {
+        Self {
+            density: PixelDensity::WorldSpace { pixels_per_unit },
+            ..Self::splat(64)
+        }
+    }
+
+    /// Creates a new `Pixelate` component where every rendered pixel covers roughly the given number of screen pixels,
+    /// so that targets look equally pixelated regardless of their distance to the camera.
+    pub fn screen_space(screen_pixels_per_pixel: f32) -> Self {
+        Self {
+            density: PixelDensity::ScreenSpace {
+                screen_pixels_per_pixel,
+            },
+            ..Self::splat(64)
+        }
+    }
+
+    /// Creates a new `Pixelate` component where every rendered pixel covers exactly
+    /// `screen_pixels_per_pixel` × `screen_pixels_per_pixel` physical pixels on screen,
+    /// and the canvas is snapped to the screen's pixel grid.
+    pub fn pixel_perfect(screen_pixels_per_pixel: u32) -> Self {
+        Self {
+            density: PixelDensity::PixelPerfect {
+                screen_pixels_per_pixel,
+            },
+            ..Self::splat(64)
+        }
+    }
+
+    /// Enables [`Pixelate::texel_snapping`].
+    pub fn with_texel_snapping(mut self) -> Self {
+        self.texel_snapping = true;
+        self
+    }
+
+    /// Enables [`Pixelate::depth_compositing`].
+    pub fn with_depth_compositing(mut self) -> Self {
+        self.depth_compositing = true;
+        self
+    }
+
+    /// Sets how the canvas blends with what is behind it.
+    pub fn with_alpha_mode(mut self, alpha_mode: CanvasAlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Sets the projection of the camera that renders the target.
+    pub fn with_projection(mut self, projection: PixelationProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Sets how much of the camera's view the target fills.
+    pub fn with_framing(mut self, framing: CanvasFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets how the bounds that determine the canvas size are computed.
+    pub fn with_bounds(mut self, bounds: BoundsMode) -> Self {
+        self.bounds = bounds;
+        self
+    }
+}
+
+/// How the size of the render texture of a target is determined.
+/// Derived sizes are rounded up to a multiple of 8 pixels, and the texture is only reallocated when that rounded size changes.
+#[derive(Debug, Reflect, Default, Copy, Clone, PartialEq)]
+pub enum PixelDensity {
+    /// Use [`Pixelate::horizontal_pixels`] and [`Pixelate::vertical_pixels`].
+    #[default]
+    Fixed,
+    /// Derive the size from the target's bounds, so that one world unit spans `pixels_per_unit` pixels.
+    WorldSpace {
+        /// How many pixels one world unit spans.
+        pixels_per_unit: f32,
+    },
+    /// Derive the size from the target's bounds and its distance to the main camera,
+    /// so that one rendered pixel spans `screen_pixels_per_pixel` physical pixels on screen.
+    ScreenSpace {
+        /// How many physical screen pixels one rendered pixel spans.
+        screen_pixels_per_pixel: f32,
+    },
+    /// Like [`PixelDensity::ScreenSpace`], but every rendered pixel spans exactly
+    /// `screen_pixels_per_pixel` × `screen_pixels_per_pixel` physical pixels on screen.
+    /// To achieve this, the canvas is scaled up slightly and snapped to the screen's pixel grid,
+    /// which keeps the pixels from swimming as the target moves.
+    PixelPerfect {
+        /// How many physical screen pixels one rendered pixel spans.
+        screen_pixels_per_pixel: u32,
+    },
+}
+
+/// The projection of the camera that renders a target.
+/// Unless noted otherwise, the camera is framed so that the bounding sphere of the target exactly fills the render texture.
+#[derive(Debug, Reflect, Copy, Clone, PartialEq)]
+pub enum PixelationProjection {
+    /// A perspective projection with the given vertical field of view in radians.
+    /// The camera is placed exactly far enough away for the bounding sphere to touch the edges of the frustum.
+    Perspective {
+        /// The vertical field of view in radians.
+        fov: f32,
+    },
+    /// An orthographic projection, which avoids any perspective mismatch between the canvas and the world.
+    Orthographic,
+    /// Renders from the position of the main camera through the canvas, using an off-axis projection.
+    /// The pixelated target looks exactly like it would unpixelated, just at a lower resolution.
+    /// [`Pixelate::texel_snapping`] has no effect with this projection.
+    OffAxis,
+}
+
+impl Default for PixelationProjection {
+    fn default() -> Self {
+        Self::Perspective {
+            fov: std::f32::consts::FRAC_PI_4,
+        }
+    }
+}
+
+/// How the camera that renders a target is framed, see [`Pixelate::framing`].
+#[derive(Debug, Reflect, Default, Copy, Clone, PartialEq, Eq)]
+pub enum CanvasFraming {
+    /// The bounding sphere of the target fills the render texture, and the canvas is a square.
+    /// This is cheap and keeps the framing steady while the target rotates,
+    /// but stretches the image if [`Pixelate::horizontal_pixels`] and [`Pixelate::vertical_pixels`] differ.
+    #[default]
+    Sphere,
+    /// Every frame, the bounds of the target are projected into the camera's view, and the camera is framed to the
+    /// smallest rectangle around them that has the aspect ratio of the render texture.
+    /// The canvas takes the shape of that rectangle, so texels are always square and tall or flat targets waste fewer of them.
+    /// Derived [`PixelDensity`] sizes follow the shape of the rectangle as well.
+    /// Falls back to [`CanvasFraming::Sphere`] with [`PixelationProjection::OffAxis`].
+    Tight,
+}
+
+/// Remaps the brightness of the image rendered for the pixelated target it is placed on,
+/// which gives the lighting a hand-painted look. This turns smooth shading into the discrete steps of cel shading.
+/// Since it works on the final colors, darker colors of the target are treated like shadows as well.
+/// It is applied before the colors are reduced to a [`PixelPaletteHandle`] and before the [`PixelOutline`] is drawn.
+#[derive(Debug, Component, Reflect, Default, Clone, PartialEq)]
+#[reflect(Component)]
+pub enum LightingRamp {
+    /// Keeps the brightness as it is.
+    #[default]
+    Smooth,
+    /// Snaps the perceived brightness to the center of one of the given number of equally sized bands.
+    Bands(u32),
+    /// Looks up the new color in the given ramp texture, whose first row goes from the darkest on the left to the brightest on the right.
+    /// The color of the ramp is multiplied with the hue of the rendered color, so a gradient from black to white
+    /// leaves the image unchanged, while a gradient with hard steps gives hand-painted bands that can also tint the shadows.
+    Texture(Handle<Image>),
+}
+
+/// A limited set of colors that the image of a pixelated target is reduced to, see [`PixelPaletteHandle`].
+/// Can be loaded from a `.gpl` (GIMP) palette, a `.hex` palette with one `RRGGBB` color per line,
+/// or a swatch image whose distinct opaque colors form the palette.
+/// Files ending in `.palette.png` are loaded as palettes automatically; other images need to be loaded with
+/// `asset_server.load::<PixelPalette>(path)`.
+#[derive(Debug, Asset, TypePath, Clone, PartialEq, Default)]
+pub struct PixelPalette {
+    /// The colors of the palette. Their alpha is ignored.
+    pub colors: Vec<Color>,
+}
+
+impl PixelPalette {
+    /// Creates a palette from the given colors.
+    pub fn new(colors: impl IntoIterator<Item = impl Into<Color>>) -> Self {
+        Self {
+            colors: colors.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Maps every pixel of the pixelated target it is placed on to the nearest color of a [`PixelPalette`].
+/// Add a [`PixelDither`] to avoid banding.
+/// Colors are compared in the Oklab color space, which matches how different colors look.
+#[derive(Debug, Component, Reflect, Clone, Default, PartialEq, Deref, DerefMut)]
+#[reflect(Component)]
+pub struct PixelPaletteHandle(pub Handle<PixelPalette>);
+
+/// Dithers the colors of the pixelated target it is placed on before they are reduced,
+/// which hides the banding that reducing colors causes on smooth shading.
+/// The colors are reduced to the target's [`PixelPaletteHandle`], or without one, to `levels` values per color channel.
+/// The pattern is anchored to the pixels of the rendered image, so it does not swim as the canvas turns.
+#[derive(Debug, Component, Reflect, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct PixelDither {
+    /// The pattern of thresholds that decides which way a color is pushed.
+    pub pattern: DitherPattern,
+    /// How far colors are pushed by the pattern, in sRGB units between 0 and 1.
+    /// This should be around the distance between neighboring colors of the palette.
+    pub spread: f32,
+    /// How many values every color channel is reduced to when the target has no [`PixelPaletteHandle`].
+    pub levels: u32,
+}
+
+impl Default for PixelDither {
+    fn default() -> Self {
+        Self {
+            pattern: DitherPattern::default(),
+            spread: 0.125,
+            levels: 8,
+        }
+    }
+}
+
+impl PixelDither {
+    /// Creates a dither with the given pattern and default settings.
+    pub fn new(pattern: DitherPattern) -> Self {
+        Self {
+            pattern,
+            ..default()
+        }
+    }
+}
+
+/// The pattern used by [`PixelDither`].
+#[derive(Debug, Reflect, Default, Clone, PartialEq)]
+pub enum DitherPattern {
+    /// A 2×2 Bayer matrix, which gives a coarse checkerboard look.
+    Bayer2,
+    /// A 4×4 Bayer matrix.
+    #[default]
+    Bayer4,
+    /// An 8×8 Bayer matrix, which gives the smoothest gradients of the ordered patterns.
+    Bayer8,
+    /// A tileable blue noise texture, whose red channel holds the thresholds.
+    /// Looks less regular than the Bayer matrices.
+    BlueNoise(Handle<Image>),
+}
+
+/// Draws a pixel-art outline around the pixelated target it is placed on.
+/// The outline is drawn into the rendered image after its colors are reduced, so it is always exactly `width` pixels wide
+/// and keeps its color regardless of any [`PixelPaletteHandle`].
+/// Outer outlines need some transparent room around the target, so they are cut off where the target touches the edge of its image.
+#[derive(Debug, Component, Reflect, Copy, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct PixelOutline {
+    /// The color of the outline. Its alpha blends inner outlines with the target and makes outer outlines translucent.
+    pub color: Color,
+    /// How many pixels wide the outline around the silhouette is. Use 0 to only draw [`PixelOutline::inner_edges`].
+    pub width: u32,
+    /// Whether the outline is drawn around the silhouette or on its outermost pixels.
+    pub placement: OutlinePlacement,
+    /// Additionally draws one pixel wide lines inside the silhouette.
+    pub inner_edges: Option<InnerEdges>,
+}
+
+impl Default for PixelOutline {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            width: 1,
+            placement: OutlinePlacement::default(),
+            inner_edges: None,
+        }
+    }
+}
+
+impl PixelOutline {
+    /// Creates a one pixel wide outer outline with the given color.
+    pub fn new(color: impl Into<Color>) -> Self {
+        Self {
+            color: color.into(),
+            ..default()
+        }
+    }
+
+    /// Sets how many pixels wide the outline around the silhouette is.
+    pub fn with_width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets whether the outline is drawn around the silhouette or on its outermost pixels.
+    pub fn with_placement(mut self, placement: OutlinePlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Additionally draws lines inside the silhouette.
+    pub fn with_inner_edges(mut self, inner_edges: InnerEdges) -> Self {
+        self.inner_edges = Some(inner_edges);
+        self
+    }
+}
+
+/// Where the outline of a [`PixelOutline`] is drawn.
+#[derive(Debug, Reflect, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OutlinePlacement {
+    /// On the transparent pixels around the silhouette, which makes the target appear bigger.
+    #[default]
+    Outer,
+    /// On the outermost pixels of the silhouette, which keeps the size of the target.
+    Inner,
+}
+
+/// The lines a [`PixelOutline`] draws inside the silhouette.
+/// These need a depth or normal prepass on the pixelation camera, which the plugin adds.
+/// Only opaque and alpha masked meshes are part of the prepass.
+#[derive(Debug, Reflect, Copy, Clone, PartialEq)]
+pub enum InnerEdges {
+    /// Draws a line on pixels whose neighbors are farther away,
+    /// which outlines parts of the target that overlap other parts.
+    Depth {
+        /// How much farther away a neighbor needs to be, relative to the distance of the pixel.
+        threshold: f32,
+    },
+    /// Draws a line where the surface of the target bends,
+    /// which also outlines creases that depth alone misses.
+    Normal {
+        /// How much the normals of neighboring pixels need to differ, between 0 for parallel and 2 for opposite normals.
+        threshold: f32,
+    },
+}
+
+/// How the canvas of a target blends with what is behind it.
+#[derive(Debug, Reflect, Default, Copy, Clone, PartialEq)]
+pub enum CanvasAlphaMode {
+    /// Blends the canvas with what is behind it.
+    /// Canvases are transparent and sorted as a whole, so they can sort incorrectly
+    /// against each other and against other transparent geometry when they overlap.
+    #[default]
+    Blend,
+    /// Discards every pixel with an alpha below the given threshold and draws the rest fully opaque.
+    /// The canvas is drawn with the opaque geometry and takes part in the depth prepass, so it always sorts correctly.
+    /// As a bonus, the edges of the pixelated target stay hard.
+    Mask(f32),
+    /// Like [`CanvasAlphaMode::Mask`], but uses the alpha as MSAA coverage,
+    /// which softens the edges of the canvas if the main camera uses MSAA.
+    AlphaToCoverage,
+}
+
+impl From<CanvasAlphaMode> for AlphaMode {
+    fn from(alpha_mode: CanvasAlphaMode) -> Self {
+        match alpha_mode {
+            CanvasAlphaMode::Blend => AlphaMode::Blend,
+            CanvasAlphaMode::Mask(threshold) => AlphaMode::Mask(threshold),
+            CanvasAlphaMode::AlphaToCoverage => AlphaMode::AlphaToCoverage,
+        }
+    }
+}
+
+/// How the [`PixelationBounds`] of a target are computed.
+#[derive(Debug, Reflect, Default, Copy, Clone, PartialEq)]
+pub enum BoundsMode {
+    /// Use the bounds of the meshes as they are when the target is first pixelated.
+    /// Animations that move vertices outside of these bounds will be clipped.
+    #[default]
+    Static,
+    /// Recompute the bounds every frame from the positions of the joints of all skinned meshes in the target's hierarchy.
+    /// Since joints sit inside the mesh, the bounds are grown by `padding` in every direction, in the target's local units.
+    /// Falls back to [`BoundsMode::Static`] if the target has no skinned meshes.
+    Joints {
+        /// How far to grow the bounds around the joints.
+        padding: f32,
+    },
+    /// Use the given bounds, in the target's local space.
+    Override(Aabb),
+}
+
+/// The bounds of a pixelated target in its local space, inserted by the plugin once the target's mesh or scene is ready.
+/// This is the union of the bounds of every mesh in the target's hierarchy and determines the size of its canvases.
+/// Depending on [`Pixelate::bounds`], this is updated every frame.
+#[derive(Debug, Component, Reflect, Copy, Clone, PartialEq, Deref)]
+#[reflect(Component)]
+pub struct PixelationBounds(pub Aabb);
+
+/// Sent when an entity marked with [`Pixelate`] cannot be pixelated.
+/// The plugin never panics because of such an entity; depending on the [`PixelationErrorReason`],
+/// the entity is either retried every frame or ignored until `Pixelate` is added again.
+#[derive(Debug, Event, Copy, Clone, PartialEq, Eq)]
+pub struct PixelationError {
+    /// The entity holding the [`Pixelate`] component.
+    pub entity: Entity,
+    /// Why the entity could not be pixelated.
+    pub reason: PixelationErrorReason,
+}
+
+/// The reason an entity could not be pixelated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PixelationErrorReason {
+    /// The entity has neither a `Mesh3d` nor a `SceneRoot`.
+    /// The entity stays queued, so adding either of them later will pixelate it.
+    MissingMeshOrScene,
+    /// The scene finished spawning, but contains no `Mesh3d`.
+    /// The entity is no longer queued.
+    SceneWithoutMesh,
+    /// The mesh has no vertex positions, so its bounds cannot be computed.
+    /// The entity stays queued, so the mesh can still be filled in later.
+    MeshWithoutBounds,
+    /// The entity was pixelated, but lost a component the plugin needs, such as its `GlobalTransform` or [`PixelationBounds`],
+    /// or gained the component that tracks the main camera.
+    /// Its canvases and pixelation cameras are despawned, and spawned again once the entity is valid again.
+    /// The entity itself is left untouched.
+    InvalidTarget,
+}
+
+impl std::fmt::Display for PixelationErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingMeshOrScene => write!(
+                f,
+                "the Pixelate component can only be added to entities with a Mesh3d or a SceneRoot, but found neither"
+            ),
+            Self::SceneWithoutMesh => write!(f, "the scene does not contain any Mesh3d"),
+            Self::MeshWithoutBounds => write!(f, "the mesh has no vertex positions to compute bounds from"),
+            Self::InvalidTarget => write!(
+                f,
+                "the entity is missing a component needed for pixelation or is also a main camera"
+            ),
         }
     }
 }
 
 /// Marks the main pass plane, to which the texture is applied.
+/// There is one canvas per pixelated target and main camera.
 #[derive(Debug, Component, Copy, Clone)]
 struct Canvas {
     pub(crate) target: Entity,
+    pub(crate) main_camera: Entity,
+    /// The pixelation camera rendering the texture shown on this canvas.
+    pub(crate) pixelation_camera: Entity,
 }
 
 #[derive(Debug, Component, Copy, Clone)]
 struct PixelationCamera {
     pub(crate) target: Entity,
+    pub(crate) main_camera: Entity,
+    /// The canvas showing what this camera renders.
+    pub(crate) canvas: Entity,
+    /// How far the canvas needs to move to compensate for the camera being snapped to the texel grid.
+    pub(crate) canvas_offset: Vec3,
+    /// With [`CanvasFraming::Tight`], the size of the tight rectangle around the target in the camera's view,
+    /// which the size of the render texture is derived from.
+    pub(crate) tight_frame: Option<Vec2>,
+    /// With [`CanvasFraming::Tight`], the size of the area the camera sees at the target, which the canvas is scaled to.
+    pub(crate) canvas_size: Option<Vec2>,
 }
 
-/// The render layers used by the plugin. All objects that will be pixelated are rendered on these layers.
+/// The render layers that pixelated objects are rendered on by default, see [`PixelationLayers::shared`].
 /// If you want light to affect them, you need to add the light to the same layers.
 pub const PIXELATION_RENDER_LAYERS: RenderLayers = RenderLayers::layer(1);
 
+/// Configures which render layers the plugin uses, see [`PixelateMeshPlugin::with_layers`].
+/// Changes only affect targets that are pixelated afterwards.
+#[derive(Debug, Resource, Reflect, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct PixelationLayers {
+    /// The layers that pixelated targets and the cameras rendering them are on.
+    /// Lights need to be on these layers to affect pixelated targets.
+    pub shared: RenderLayers,
+    /// Whether every pixelated target gets a render layer of its own instead of [`PixelationLayers::shared`],
+    /// so that a light can be scoped to a single target. The layer of a target is in its [`PixelationRenderLayers`].
+    pub per_target: bool,
+    /// The first render layer the plugin may hand out, both for targets with their own layer
+    /// and for the layers that main cameras see their canvases on.
+    /// All layers from this one onward are reserved for the plugin.
+    pub first_allocated_layer: usize,
+    /// Whether to keep every [`DirectionalLight`], [`PointLight`] and [`SpotLight`] that is on any other layer
+    /// on the layers of all pixelated targets as well, so that pixelated targets are lit like the rest of the world.
+    /// Lights that are only on layers of the plugin are left alone, so they can still be scoped to pixelated targets.
+    pub mirror_lights: bool,
+}
+
+impl Default for PixelationLayers {
+    fn default() -> Self {
+        Self {
+            shared: PIXELATION_RENDER_LAYERS,
+            per_target: false,
+            first_allocated_layer: 2,
+            mirror_lights: false,
+        }
+    }
+}
+
+/// Which components pixelation cameras copy from their main camera and keep in sync with it,
+/// see [`PixelateMeshPlugin::with_inherited_camera_settings`].
+/// All of them are copied by default, so pixelated targets are lit and exposed like the rest of the world.
+/// Disabling one stops syncing it, but leaves the last copied value in place.
+#[derive(Debug, Resource, Reflect, Copy, Clone, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct InheritedCameraSettings {
+    /// Whether to copy the [`EnvironmentMapLight`].
+    pub environment_map: bool,
+    /// Whether to copy the [`AmbientLight`] that overrides the global one for the camera.
+    pub ambient_light: bool,
+    /// Whether to copy the [`Tonemapping`](bevy::core_pipeline::tonemapping::Tonemapping).
+    pub tonemapping: bool,
+    /// Whether to copy the [`Exposure`](bevy::render::camera::Exposure).
+    pub exposure: bool,
+    /// Whether to copy the [`DistanceFog`].
+    /// Fog depends on the distance to the camera, which only matches the main camera with [`PixelationProjection::OffAxis`].
+    pub fog: bool,
+    /// Whether to copy the [`ColorGrading`](bevy::render::view::ColorGrading).
+    pub color_grading: bool,
+}
+
+impl Default for InheritedCameraSettings {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl InheritedCameraSettings {
+    /// Copies all supported components.
+    pub const ALL: Self = Self {
+        environment_map: true,
+        ambient_light: true,
+        tonemapping: true,
+        exposure: true,
+        fog: true,
+        color_grading: true,
+    };
+
+    /// Copies nothing, leaving the pixelation cameras with Bevy's defaults.
+    pub const NONE: Self = Self {
+        environment_map: false,
+        ambient_light: false,
+        tonemapping: false,
+        exposure: false,
+        fog: false,
+        color_grading: false,
+    };
+}
+
+/// The render layers a pixelated target is rendered on, inserted by the plugin once the target is pixelated.
+/// Add these to a light to make it affect the target.
+#[derive(Debug, Component, Reflect, Clone, PartialEq, Deref)]
+#[reflect(Component)]
+pub struct PixelationRenderLayers(pub RenderLayers);
+
 #[cfg(doctest)]
 #[doc = include_str!("../readme.md")]
 mod test_readme {}
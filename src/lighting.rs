@@ -0,0 +1,133 @@
+use crate::fullscreen::{
+    FullscreenNode, FullscreenPass, FullscreenPassPlugin, LightingRampSettings,
+};
+use crate::quantize::QuantizeLabel;
+use crate::{LightingRamp, PixelationCamera};
+use bevy::asset::embedded_asset;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::core_pipeline::prepass::ViewPrepassTextures;
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_asset::RenderAssets,
+    render_graph::{RenderGraphApp, RenderLabel, ViewNodeRunner},
+    render_resource::{
+        binding_types::texture_2d, BindGroupLayoutEntryBuilder, ShaderDefVal, TextureSampleType,
+        TextureView,
+    },
+    texture::{FallbackImage, GpuImage},
+    RenderApp,
+};
+
+/// Remaps the brightness of the images rendered by pixelation cameras, see [`LightingRamp`].
+/// Shared by all instances of [`PixelateMeshPlugin`](crate::PixelateMeshPlugin).
+pub(crate) struct LightingRampPlugin;
+
+impl Plugin for LightingRampPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/lighting_ramp.wgsl");
+        app.add_plugins((
+            ExtractComponentPlugin::<PixelationLightingRamp>::default(),
+            FullscreenPassPlugin::<PixelationLightingRamp>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<FullscreenNode<PixelationLightingRamp>>>(
+                Core3d,
+                LightingRampLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::Tonemapping, LightingRampLabel, QuantizeLabel),
+            );
+    }
+}
+
+/// Placed on pixelation cameras whose target has a [`LightingRamp`] other than [`LightingRamp::Smooth`].
+#[derive(Debug, Component, Clone, PartialEq, Deref)]
+pub(crate) struct PixelationLightingRamp(pub(crate) LightingRamp);
+
+/// Keeps the [`PixelationLightingRamp`] of every pixelation camera in sync with the lighting of its target.
+pub(crate) fn update_lighting_ramps(
+    mut commands: Commands,
+    pixelation_camera_query: Query<(Entity, &PixelationCamera, Option<&PixelationLightingRamp>)>,
+    target_query: Query<&LightingRamp>,
+) {
+    for (entity, pixelation_camera, current) in &pixelation_camera_query {
+        let lighting = target_query
+            .get(pixelation_camera.target)
+            .ok()
+            .filter(|lighting| **lighting != LightingRamp::Smooth);
+        if lighting == current.map(|current| &current.0) {
+            continue;
+        }
+        match lighting {
+            Some(lighting) => commands
+                .entity(entity)
+                .insert(PixelationLightingRamp(lighting.clone())),
+            None => commands.entity(entity).remove::<PixelationLightingRamp>(),
+        };
+    }
+}
+
+impl ExtractComponent for PixelationLightingRamp {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = (Self, LightingRampSettings);
+
+    fn extract_component(lighting: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        let bands = match lighting.0 {
+            LightingRamp::Bands(bands) => bands.max(1) as f32,
+            _ => 0.,
+        };
+        Some((lighting.clone(), LightingRampSettings { bands }))
+    }
+}
+
+impl FullscreenPass for PixelationLightingRamp {
+    type Settings = LightingRampSettings;
+    /// Whether the ramp is a texture.
+    type Key = bool;
+
+    const LABEL: &'static str = "pixelation_lighting_ramp";
+    const SHADER: &'static str = "embedded://pixelate_mesh/shaders/lighting_ramp.wgsl";
+
+    fn texture_layouts() -> Vec<Vec<BindGroupLayoutEntryBuilder>> {
+        // The ramp texture
+        vec![vec![texture_2d(TextureSampleType::Float {
+            filterable: false,
+        })]]
+    }
+
+    fn shader_defs(texture: Self::Key) -> Vec<ShaderDefVal> {
+        if texture {
+            vec!["RAMP_TEXTURE".into()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn key(&self, _msaa: &Msaa) -> Self::Key {
+        matches!(self.0, LightingRamp::Texture(_))
+    }
+
+    fn textures<'w>(
+        &'w self,
+        _prepass: Option<&'w ViewPrepassTextures>,
+        world: &'w World,
+    ) -> Option<Vec<&'w TextureView>> {
+        let ramp = match &self.0 {
+            // `None` while the ramp texture is still loading.
+            LightingRamp::Texture(ramp) => world.resource::<RenderAssets<GpuImage>>().get(ramp)?,
+            _ => &world.resource::<FallbackImage>().d2,
+        };
+        Some(vec![&ramp.texture_view])
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct LightingRampLabel;
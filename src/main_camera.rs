@@ -0,0 +1,124 @@
+use crate::creation::{spawn_pixelation_pair, CanvasMeshHandle, Ordering};
+use crate::density::canvas_image_size;
+use crate::depth::{CanvasDepthMaterial, PixelationDepth};
+use crate::layers::AllocatedLayers;
+use crate::pool::RenderTargetPool;
+use crate::{
+    Canvas, CanvasMaterial, Pixelate, PixelationBounds, PixelationCamera, PixelationLayers,
+    PixelationRenderLayers,
+};
+use bevy::platform_support::collections::HashSet;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+/// Makes sure every pixelated target has a canvas and pixelation camera for every main camera.
+pub(crate) fn spawn_missing_pixelation_pairs<C: Component, M: CanvasMaterial>(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<M>>,
+    mut depth_materials: ResMut<Assets<CanvasDepthMaterial>>,
+    canvas_mesh: Res<CanvasMeshHandle>,
+    mut images: ResMut<Assets<Image>>,
+    mut pool: ResMut<RenderTargetPool>,
+    mut ordering: ResMut<Ordering>,
+    mut allocated_layers: ResMut<AllocatedLayers>,
+    layer_config: Res<PixelationLayers>,
+    main_camera_query: Query<
+        (
+            Entity,
+            Option<&RenderLayers>,
+            &Camera,
+            &Projection,
+            &GlobalTransform,
+        ),
+        (With<C>, Without<Pixelate>),
+    >,
+    target_query: Query<
+        (
+            Entity,
+            &Pixelate,
+            &PixelationBounds,
+            &PixelationRenderLayers,
+            &GlobalTransform,
+        ),
+        Without<C>,
+    >,
+    canvas_query: Query<&Canvas>,
+) {
+    let existing: HashSet<_> = canvas_query
+        .iter()
+        .map(|canvas| (canvas.target, canvas.main_camera))
+        .collect();
+    for (main_camera, render_layers, camera, projection, camera_transform) in &main_camera_query {
+        let layer = match allocated_layers.get(main_camera) {
+            Some(layer) => layer,
+            None => {
+                debug!("Assigning a canvas render layer to a new main camera");
+                let layer = allocated_layers.allocate(main_camera, &layer_config);
+                let render_layers = render_layers.cloned().unwrap_or_default().with(layer);
+                commands.entity(main_camera).insert(render_layers);
+                layer
+            }
+        };
+        for (target, pixelate, bounds, target_layers, target_transform) in &target_query {
+            if existing.contains(&(target, main_camera)) {
+                continue;
+            }
+            let image_size = canvas_image_size(
+                pixelate,
+                bounds,
+                target_transform,
+                Some((camera, projection, camera_transform)),
+                None,
+            );
+            spawn_pixelation_pair(
+                &mut commands,
+                &mut materials,
+                &mut depth_materials,
+                &canvas_mesh,
+                &mut images,
+                &mut pool,
+                &mut ordering,
+                target,
+                pixelate,
+                image_size,
+                target_layers,
+                main_camera,
+                layer,
+            );
+        }
+    }
+}
+
+/// Tears down the canvases and pixelation cameras that belonged to a main camera that is gone.
+pub(crate) fn despawn_removed_main_cameras<C: Component>(
+    mut commands: Commands,
+    mut removed_main_cameras: RemovedComponents<C>,
+    mut allocated_layers: ResMut<AllocatedLayers>,
+    mut pool: ResMut<RenderTargetPool>,
+    render_layers_query: Query<&RenderLayers>,
+    canvas_query: Query<(Entity, &Canvas)>,
+    pixelation_camera_query: Query<(Entity, &PixelationCamera, &Camera, Option<&PixelationDepth>)>,
+) {
+    for main_camera in removed_main_cameras.read() {
+        debug!("A main camera was removed; despawning its canvases and pixelation cameras.");
+        for (entity, canvas) in &canvas_query {
+            if canvas.main_camera == main_camera {
+                commands.entity(entity).despawn();
+            }
+        }
+        for (entity, pixelation_camera, camera, depth) in &pixelation_camera_query {
+            if pixelation_camera.main_camera != main_camera {
+                continue;
+            }
+            pool.release_camera_textures(camera, depth);
+            commands.entity(entity).despawn();
+        }
+        let Some(layer) = allocated_layers.release(main_camera) else {
+            continue;
+        };
+        if let Ok(render_layers) = render_layers_query.get(main_camera) {
+            let render_layers = render_layers.clone().without(layer);
+            commands.entity(main_camera).try_insert(render_layers);
+        }
+    }
+}
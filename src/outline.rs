@@ -0,0 +1,196 @@
+use crate::fullscreen::{FullscreenNode, FullscreenPass, FullscreenPassPlugin, OutlineSettings};
+use crate::quantize::QuantizeLabel;
+use crate::{InnerEdges, OutlinePlacement, PixelOutline, PixelationCamera};
+use bevy::asset::embedded_asset;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::core_pipeline::prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures};
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_graph::{RenderGraphApp, RenderLabel, ViewNodeRunner},
+    render_resource::{
+        binding_types::{
+            texture_2d, texture_2d_multisampled, texture_depth_2d, texture_depth_2d_multisampled,
+        },
+        BindGroupLayoutEntryBuilder, ShaderDefVal, TextureSampleType, TextureView,
+    },
+    RenderApp,
+};
+
+/// Draws the outlines of [`PixelOutline`] into the images rendered by pixelation cameras.
+/// This runs on the low resolution image, so the outlines line up with its pixels.
+/// Shared by all instances of [`PixelateMeshPlugin`](crate::PixelateMeshPlugin).
+pub(crate) struct OutlinePlugin;
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/outline.wgsl");
+        app.add_plugins((
+            ExtractComponentPlugin::<PixelationOutline>::default(),
+            FullscreenPassPlugin::<PixelationOutline>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<FullscreenNode<PixelationOutline>>>(
+                Core3d,
+                OutlineLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    QuantizeLabel,
+                    OutlineLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+}
+
+/// Placed on pixelation cameras whose target has a [`PixelOutline`].
+#[derive(Debug, Component, Clone, Copy, PartialEq, Deref)]
+pub(crate) struct PixelationOutline(pub(crate) PixelOutline);
+
+/// Keeps the [`PixelationOutline`] of every pixelation camera in sync with the outline of its target,
+/// and adds the prepass needed for its inner edges.
+pub(crate) fn update_outlines(
+    mut commands: Commands,
+    pixelation_camera_query: Query<(Entity, &PixelationCamera, Option<&PixelationOutline>)>,
+    target_query: Query<&PixelOutline>,
+) {
+    for (entity, pixelation_camera, current) in &pixelation_camera_query {
+        let outline = target_query.get(pixelation_camera.target).ok().copied();
+        if outline == current.map(|current| current.0) {
+            continue;
+        }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<(PixelationOutline, DepthPrepass, NormalPrepass)>();
+        let Some(outline) = outline else {
+            continue;
+        };
+        entity_commands.insert(PixelationOutline(outline));
+        match outline.inner_edges {
+            Some(InnerEdges::Depth { .. }) => {
+                entity_commands.insert(DepthPrepass);
+            }
+            Some(InnerEdges::Normal { .. }) => {
+                entity_commands.insert(NormalPrepass);
+            }
+            None => {}
+        }
+    }
+}
+
+impl ExtractComponent for PixelationOutline {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = (Self, OutlineSettings);
+
+    fn extract_component(outline: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        let threshold = match outline.inner_edges {
+            Some(InnerEdges::Depth { threshold } | InnerEdges::Normal { threshold }) => threshold,
+            None => 0.,
+        };
+        let settings = OutlineSettings {
+            color: outline.color.to_linear().to_vec4(),
+            width: outline.width as f32,
+            threshold,
+        };
+        Some((*outline, settings))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct OutlinePipelineKey {
+    inner: bool,
+    edges: EdgesKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum EdgesKey {
+    None,
+    Depth { multisampled: bool },
+    Normal { multisampled: bool },
+}
+
+impl FullscreenPass for PixelationOutline {
+    type Settings = OutlineSettings;
+    type Key = OutlinePipelineKey;
+
+    const LABEL: &'static str = "pixelation_outline";
+    const SHADER: &'static str = "embedded://pixelate_mesh/shaders/outline.wgsl";
+
+    fn texture_layouts() -> Vec<Vec<BindGroupLayoutEntryBuilder>> {
+        let normals = TextureSampleType::Float { filterable: false };
+        // The depth or normals of the prepass
+        vec![
+            vec![],
+            vec![texture_depth_2d()],
+            vec![texture_depth_2d_multisampled()],
+            vec![texture_2d(normals)],
+            vec![texture_2d_multisampled(normals)],
+        ]
+    }
+
+    fn layout_index(key: Self::Key) -> usize {
+        match key.edges {
+            EdgesKey::None => 0,
+            EdgesKey::Depth { multisampled } => 1 + multisampled as usize,
+            EdgesKey::Normal { multisampled } => 3 + multisampled as usize,
+        }
+    }
+
+    fn shader_defs(key: Self::Key) -> Vec<ShaderDefVal> {
+        let mut shader_defs = vec![];
+        if key.inner {
+            shader_defs.push("INNER".into());
+        }
+        let multisampled = match key.edges {
+            EdgesKey::None => false,
+            EdgesKey::Depth { multisampled } => {
+                shader_defs.push("DEPTH_EDGES".into());
+                multisampled
+            }
+            EdgesKey::Normal { multisampled } => {
+                shader_defs.push("NORMAL_EDGES".into());
+                multisampled
+            }
+        };
+        if multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+        shader_defs
+    }
+
+    fn key(&self, msaa: &Msaa) -> Self::Key {
+        let multisampled = msaa.samples() > 1;
+        let edges = match self.inner_edges {
+            None => EdgesKey::None,
+            Some(InnerEdges::Depth { .. }) => EdgesKey::Depth { multisampled },
+            Some(InnerEdges::Normal { .. }) => EdgesKey::Normal { multisampled },
+        };
+        OutlinePipelineKey {
+            inner: self.placement == OutlinePlacement::Inner,
+            edges,
+        }
+    }
+
+    fn textures<'w>(
+        &'w self,
+        prepass: Option<&'w ViewPrepassTextures>,
+        _world: &'w World,
+    ) -> Option<Vec<&'w TextureView>> {
+        // The prepass is added a frame after the inner edges.
+        match self.inner_edges {
+            None => Some(vec![]),
+            Some(InnerEdges::Depth { .. }) => Some(vec![prepass?.depth_view()?]),
+            Some(InnerEdges::Normal { .. }) => Some(vec![prepass?.normal_view()?]),
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct OutlineLabel;
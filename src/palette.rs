@@ -0,0 +1,291 @@
+use crate::{PixelPalette, PixelPaletteHandle};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, LoadDirectError, RenderAssetUsages};
+use bevy::platform_support::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+/// Loads a [`PixelPalette`] from a `.png` swatch strip, a GIMP `.gpl` palette or a `.hex` palette.
+/// For images, every distinct opaque color is part of the palette, in reading order.
+/// Since `.png` is already taken by images, only `.palette.png` is picked by default;
+/// load other images with `asset_server.load::<PixelPalette>(path)`.
+#[derive(Debug, Default)]
+pub(crate) struct PixelPaletteLoader;
+
+/// An error that occurred while loading a [`PixelPalette`].
+#[derive(Debug)]
+pub(crate) enum PixelPaletteLoaderError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The swatch image could not be loaded.
+    Image(Box<LoadDirectError>),
+    /// A line of a `.gpl` or `.hex` file is not a color.
+    InvalidLine(usize),
+    /// The file does not contain any colors.
+    Empty,
+}
+
+impl std::fmt::Display for PixelPaletteLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read the palette: {error}"),
+            Self::Image(error) => write!(f, "could not load the palette image: {error}"),
+            Self::InvalidLine(line) => write!(f, "line {line} of the palette is not a color"),
+            Self::Empty => write!(f, "the palette does not contain any colors"),
+        }
+    }
+}
+
+impl std::error::Error for PixelPaletteLoaderError {}
+
+impl From<std::io::Error> for PixelPaletteLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl AssetLoader for PixelPaletteLoader {
+    type Asset = PixelPalette;
+    type Settings = ();
+    type Error = PixelPaletteLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let extension = load_context
+            .path()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let colors = match extension.as_deref() {
+            Some("gpl") => parse_lines(&read_to_string(reader).await?, parse_gpl_line)?,
+            Some("hex") => parse_lines(&read_to_string(reader).await?, parse_hex_line)?,
+            _ => {
+                let path = load_context.path().to_owned();
+                let image = load_context
+                    .loader()
+                    .immediate()
+                    .load::<Image>(path)
+                    .await
+                    .map_err(|error| PixelPaletteLoaderError::Image(Box::new(error)))?;
+                colors_of_image(&image.take())
+            }
+        };
+        into_palette(colors)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gpl", "hex", "palette.png"]
+    }
+}
+
+async fn read_to_string(reader: &mut dyn Reader) -> Result<String, PixelPaletteLoaderError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    String::from_utf8(bytes)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error).into())
+}
+
+fn into_palette(colors: Vec<Color>) -> Result<PixelPalette, PixelPaletteLoaderError> {
+    if colors.is_empty() {
+        return Err(PixelPaletteLoaderError::Empty);
+    }
+    Ok(PixelPalette { colors })
+}
+
+/// Parses every line into a color, skipping lines for which `parse_line` returns `Ok(None)`.
+fn parse_lines(
+    text: &str,
+    parse_line: impl Fn(&str) -> Result<Option<Color>, ()>,
+) -> Result<Vec<Color>, PixelPaletteLoaderError> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            parse_line(line.trim())
+                .map_err(|_| PixelPaletteLoaderError::InvalidLine(index + 1))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Parses a line like `255 128 0 Orange`, skipping the header and comments.
+fn parse_gpl_line(line: &str) -> Result<Option<Color>, ()> {
+    let is_header = ["GIMP Palette", "Name:", "Columns:"]
+        .iter()
+        .any(|header| line.starts_with(header));
+    if line.is_empty() || line.starts_with('#') || is_header {
+        return Ok(None);
+    }
+    let mut channels = line.split_whitespace().map(str::parse::<u8>);
+    match (channels.next(), channels.next(), channels.next()) {
+        (Some(Ok(red)), Some(Ok(green)), Some(Ok(blue))) => {
+            Ok(Some(Color::srgb_u8(red, green, blue)))
+        }
+        _ => Err(()),
+    }
+}
+
+/// Parses a line like `ff8000`.
+fn parse_hex_line(line: &str) -> Result<Option<Color>, ()> {
+    if line.is_empty() {
+        return Ok(None);
+    }
+    Srgba::hex(line)
+        .map(|color| Some(color.into()))
+        .map_err(|_| ())
+}
+
+fn colors_of_image(image: &Image) -> Vec<Color> {
+    let mut seen = HashSet::new();
+    let mut colors = Vec::new();
+    for y in 0..image.height() {
+        for x in 0..image.width() {
+            let Ok(color) = image.get_color_at(x, y) else {
+                continue;
+            };
+            let color = color.to_srgba();
+            if color.alpha < 0.5 {
+                continue;
+            }
+            if seen.insert(color.to_u8_array()) {
+                colors.push(color.with_alpha(1.).into());
+            }
+        }
+    }
+    colors
+}
+
+/// The palettes as images, so they can be read on the GPU.
+#[derive(Debug, Resource, Default)]
+pub(crate) struct PaletteImages(HashMap<AssetId<PixelPalette>, Handle<Image>>);
+
+impl PaletteImages {
+    pub(crate) fn get(&self, palette: &PixelPaletteHandle) -> Option<&Handle<Image>> {
+        self.0.get(&palette.id())
+    }
+}
+
+/// Keeps [`PaletteImages`] in sync with the [`PixelPalette`] assets.
+pub(crate) fn update_palette_images(
+    mut palette_events: EventReader<AssetEvent<PixelPalette>>,
+    palettes: Res<Assets<PixelPalette>>,
+    mut palette_images: ResMut<PaletteImages>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for event in palette_events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(palette) = palettes
+                    .get(id)
+                    .filter(|palette| !palette.colors.is_empty())
+                else {
+                    continue;
+                };
+                let image = images.add(create_palette_image(palette));
+                palette_images.0.insert(id, image);
+            }
+            AssetEvent::Removed { id } => {
+                palette_images.0.remove(&id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lays out the colors of the palette in a row of texels.
+fn create_palette_image(palette: &PixelPalette) -> Image {
+    let data = palette
+        .colors
+        .iter()
+        .flat_map(|color| color.to_srgba().to_u8_array())
+        .collect();
+    Image::new(
+        Extent3d {
+            width: palette.colors.len() as u32,
+            height: 1,
+            ..default()
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpl_skips_header_and_comments() {
+        let text = "GIMP Palette\nName: Test\nColumns: 2\n# A comment\n\n255 128 0 Orange\n  0   0 255\tBlue\n";
+        let colors = parse_lines(text, parse_gpl_line).unwrap();
+        assert_eq!(
+            colors,
+            [Color::srgb_u8(255, 128, 0), Color::srgb_u8(0, 0, 255)]
+        );
+    }
+
+    #[test]
+    fn gpl_reports_invalid_line() {
+        let text = "GIMP Palette\n255 128 0\n255 128\n";
+        let error = parse_lines(text, parse_gpl_line).unwrap_err();
+        assert!(matches!(error, PixelPaletteLoaderError::InvalidLine(3)));
+
+        let text = "256 0 0\n";
+        let error = parse_lines(text, parse_gpl_line).unwrap_err();
+        assert!(matches!(error, PixelPaletteLoaderError::InvalidLine(1)));
+    }
+
+    #[test]
+    fn hex_parses_colors_with_and_without_hash() {
+        let text = "ff8000\n\n#0000FF\n";
+        let colors = parse_lines(text, parse_hex_line).unwrap();
+        assert_eq!(
+            colors,
+            [Color::srgb_u8(255, 128, 0), Color::srgb_u8(0, 0, 255)]
+        );
+    }
+
+    #[test]
+    fn hex_reports_invalid_line() {
+        let error = parse_lines("ff8000\nnot a color\n", parse_hex_line).unwrap_err();
+        assert!(matches!(error, PixelPaletteLoaderError::InvalidLine(2)));
+    }
+
+    #[test]
+    fn palette_without_colors_is_empty() {
+        let colors = parse_lines("GIMP Palette\n# Nothing here\n", parse_gpl_line).unwrap();
+        let error = into_palette(colors).unwrap_err();
+        assert!(matches!(error, PixelPaletteLoaderError::Empty));
+    }
+
+    #[test]
+    fn image_skips_duplicate_and_transparent_pixels() {
+        let pixels: [[u8; 4]; 5] = [
+            [255, 0, 0, 255],
+            [0, 0, 255, 0],
+            [255, 0, 0, 255],
+            [0, 255, 0, 200],
+            [255, 0, 0, 255],
+        ];
+        let image = Image::new(
+            Extent3d {
+                width: pixels.len() as u32,
+                height: 1,
+                ..default()
+            },
+            TextureDimension::D2,
+            pixels.into_iter().flatten().collect(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::MAIN_WORLD,
+        );
+        assert_eq!(
+            colors_of_image(&image),
+            [Color::srgb_u8(255, 0, 0), Color::srgb_u8(0, 255, 0)]
+        );
+    }
+}
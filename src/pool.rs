@@ -0,0 +1,133 @@
+use crate::depth::PixelationDepth;
+use bevy::platform_support::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::TextureFormat;
+
+/// Recycles the render textures of pixelation cameras, so that changing their size,
+/// e.g. when animating [`Pixelate::horizontal_pixels`](crate::Pixelate::horizontal_pixels), does not allocate a new texture every frame.
+/// Textures that are no longer used are kept around for [`RenderTargetPool::max_idle_frames`] frames,
+/// in case a texture of the same size and format is needed again.
+#[derive(Debug, Resource)]
+pub struct RenderTargetPool {
+    /// How many frames an unused texture is kept before it is freed.
+    pub max_idle_frames: u32,
+    in_use: HashMap<AssetId<Image>, PoolKey>,
+    free: HashMap<PoolKey, Vec<FreeImage>>,
+    frame: u32,
+    created: usize,
+    reused: usize,
+}
+
+impl Default for RenderTargetPool {
+    fn default() -> Self {
+        Self {
+            max_idle_frames: 60,
+            in_use: HashMap::default(),
+            free: HashMap::default(),
+            frame: 0,
+            created: 0,
+            reused: 0,
+        }
+    }
+}
+
+/// A snapshot of how many textures a [`RenderTargetPool`] holds.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RenderTargetPoolStats {
+    /// How many textures are currently rendered to.
+    pub in_use: usize,
+    /// How many textures are unused and waiting to be reused or freed.
+    pub free: usize,
+    /// How many textures were created since the app started.
+    pub created: usize,
+    /// How many times a free texture was reused instead of creating a new one.
+    pub reused: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    size: UVec2,
+    format: TextureFormat,
+}
+
+#[derive(Debug)]
+struct FreeImage {
+    handle: Handle<Image>,
+    released_at: u32,
+}
+
+impl RenderTargetPool {
+    /// Returns how many textures the pool holds.
+    pub fn stats(&self) -> RenderTargetPoolStats {
+        RenderTargetPoolStats {
+            in_use: self.in_use.len(),
+            free: self.free.values().map(Vec::len).sum(),
+            created: self.created,
+            reused: self.reused,
+        }
+    }
+
+    /// Returns an unused texture of the given size and format, creating it with `create` if there is none.
+    pub(crate) fn acquire(
+        &mut self,
+        images: &mut Assets<Image>,
+        size: UVec2,
+        format: TextureFormat,
+        create: fn(UVec2) -> Image,
+    ) -> Handle<Image> {
+        let key = PoolKey { size, format };
+        let handle = match self.free.get_mut(&key).and_then(Vec::pop) {
+            Some(free_image) => {
+                self.reused += 1;
+                free_image.handle
+            }
+            None => {
+                self.created += 1;
+                images.add(create(size))
+            }
+        };
+        self.in_use.insert(handle.id(), key);
+        handle
+    }
+
+    /// Hands a texture from [`RenderTargetPool::acquire`] back to the pool. Other textures are ignored.
+    pub(crate) fn release(&mut self, handle: &Handle<Image>) {
+        let Some(key) = self.in_use.remove(&handle.id()) else {
+            return;
+        };
+        self.free.entry(key).or_default().push(FreeImage {
+            handle: handle.clone(),
+            released_at: self.frame,
+        });
+    }
+
+    /// Hands the textures a pixelation camera renders to back to the pool.
+    pub(crate) fn release_camera_textures(
+        &mut self,
+        camera: &Camera,
+        depth: Option<&PixelationDepth>,
+    ) {
+        if let RenderTarget::Image(image_target) = &camera.target {
+            self.release(&image_target.handle);
+        }
+        if let Some(depth) = depth {
+            self.release(&depth.texture);
+        }
+    }
+
+    /// Frees the textures that have not been used for [`RenderTargetPool::max_idle_frames`] frames.
+    fn trim(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+        let (frame, max_idle_frames) = (self.frame, self.max_idle_frames);
+        self.free.retain(|_, free_images| {
+            free_images
+                .retain(|free_image| frame.wrapping_sub(free_image.released_at) <= max_idle_frames);
+            !free_images.is_empty()
+        });
+    }
+}
+
+pub(crate) fn trim_render_target_pool(mut pool: ResMut<RenderTargetPool>) {
+    pool.trim();
+}
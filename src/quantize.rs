@@ -0,0 +1,195 @@
+use crate::fullscreen::{FullscreenNode, FullscreenPass, FullscreenPassPlugin, QuantizeSettings};
+use crate::palette::{PaletteImages, PixelPaletteLoader};
+use crate::{DitherPattern, PixelDither, PixelPalette, PixelPaletteHandle, PixelationCamera};
+use bevy::asset::embedded_asset;
+use bevy::core_pipeline::core_3d::graph::{Core3d, Node3d};
+use bevy::core_pipeline::prepass::ViewPrepassTextures;
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_asset::RenderAssets,
+    render_graph::{RenderGraphApp, RenderLabel, ViewNodeRunner},
+    render_resource::{
+        binding_types::texture_2d, BindGroupLayoutEntryBuilder, ShaderDefVal, TextureSampleType,
+        TextureView,
+    },
+    texture::{FallbackImage, GpuImage},
+    RenderApp,
+};
+
+/// Reduces and dithers the colors of the images rendered by pixelation cameras, see [`PixelPaletteHandle`] and [`PixelDither`].
+/// This runs on the low resolution image, so it works with any canvas material.
+/// Shared by all instances of [`PixelateMeshPlugin`](crate::PixelateMeshPlugin).
+pub(crate) struct QuantizePlugin;
+
+impl Plugin for QuantizePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shaders/quantize.wgsl");
+        app.init_asset::<PixelPalette>()
+            .init_asset_loader::<PixelPaletteLoader>()
+            .init_resource::<PaletteImages>()
+            .add_plugins((
+                ExtractComponentPlugin::<PixelationQuantization>::default(),
+                FullscreenPassPlugin::<PixelationQuantization>::default(),
+            ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<FullscreenNode<PixelationQuantization>>>(
+                Core3d,
+                QuantizeLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    QuantizeLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+}
+
+/// Placed on pixelation cameras whose target has a [`PixelPaletteHandle`] or [`PixelDither`].
+#[derive(Debug, Component, Clone, PartialEq)]
+pub(crate) struct PixelationQuantization {
+    /// The palette laid out as a row of texels. Without one, colors are reduced to the levels of the dither.
+    pub(crate) palette: Option<Handle<Image>>,
+    pub(crate) dither: Option<PixelDither>,
+}
+
+/// Keeps the [`PixelationQuantization`] of every pixelation camera in sync with the palette and dither of its target.
+pub(crate) fn update_quantization(
+    mut commands: Commands,
+    pixelation_camera_query: Query<(Entity, &PixelationCamera, Option<&PixelationQuantization>)>,
+    target_query: Query<(Option<&PixelPaletteHandle>, Option<&PixelDither>)>,
+    palette_images: Res<PaletteImages>,
+) {
+    for (entity, pixelation_camera, current) in &pixelation_camera_query {
+        let (palette, dither) = target_query
+            .get(pixelation_camera.target)
+            .unwrap_or((None, None));
+        let quantization = match palette.map(|palette| palette_images.get(palette)) {
+            // The palette is still loading.
+            Some(None) => None,
+            Some(Some(palette)) => Some(PixelationQuantization {
+                palette: Some(palette.clone()),
+                dither: dither.cloned(),
+            }),
+            None => dither.map(|dither| PixelationQuantization {
+                palette: None,
+                dither: Some(dither.clone()),
+            }),
+        };
+        if quantization.as_ref() == current {
+            continue;
+        }
+        match quantization {
+            Some(quantization) => commands.entity(entity).insert(quantization),
+            None => commands.entity(entity).remove::<PixelationQuantization>(),
+        };
+    }
+}
+
+impl ExtractComponent for PixelationQuantization {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = (Self, QuantizeSettings);
+
+    fn extract_component(quantization: QueryItem<'_, Self::QueryData>) -> Option<Self::Out> {
+        let settings = quantization.dither.as_ref().map_or(
+            QuantizeSettings {
+                spread: 0.,
+                levels: 0.,
+            },
+            |dither| QuantizeSettings {
+                spread: dither.spread,
+                levels: dither.levels.max(2) as f32,
+            },
+        );
+        Some((quantization.clone(), settings))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct QuantizePipelineKey {
+    palette: bool,
+    dither: DitherKey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DitherKey {
+    None,
+    Bayer(u32),
+    BlueNoise,
+}
+
+impl FullscreenPass for PixelationQuantization {
+    type Settings = QuantizeSettings;
+    type Key = QuantizePipelineKey;
+
+    const LABEL: &'static str = "pixelation_quantize";
+    const SHADER: &'static str = "embedded://pixelate_mesh/shaders/quantize.wgsl";
+
+    fn texture_layouts() -> Vec<Vec<BindGroupLayoutEntryBuilder>> {
+        vec![vec![
+            // The palette
+            texture_2d(TextureSampleType::Float { filterable: false }),
+            // The blue noise
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ]]
+    }
+
+    fn shader_defs(key: Self::Key) -> Vec<ShaderDefVal> {
+        let mut shader_defs = vec![];
+        if key.palette {
+            shader_defs.push("PALETTE".into());
+        }
+        match key.dither {
+            DitherKey::None => {}
+            DitherKey::Bayer(size) => {
+                shader_defs.push(ShaderDefVal::UInt("BAYER_SIZE".into(), size))
+            }
+            DitherKey::BlueNoise => shader_defs.push("BLUE_NOISE".into()),
+        }
+        shader_defs
+    }
+
+    fn key(&self, _msaa: &Msaa) -> Self::Key {
+        let dither = match self.dither.as_ref().map(|dither| &dither.pattern) {
+            None => DitherKey::None,
+            Some(DitherPattern::Bayer2) => DitherKey::Bayer(2),
+            Some(DitherPattern::Bayer4) => DitherKey::Bayer(4),
+            Some(DitherPattern::Bayer8) => DitherKey::Bayer(8),
+            Some(DitherPattern::BlueNoise(_)) => DitherKey::BlueNoise,
+        };
+        QuantizePipelineKey {
+            palette: self.palette.is_some(),
+            dither,
+        }
+    }
+
+    fn textures<'w>(
+        &'w self,
+        _prepass: Option<&'w ViewPrepassTextures>,
+        world: &'w World,
+    ) -> Option<Vec<&'w TextureView>> {
+        let images = world.resource::<RenderAssets<GpuImage>>();
+        let fallback = &world.resource::<FallbackImage>().d2;
+        let palette = match &self.palette {
+            Some(palette) => images.get(palette)?,
+            None => fallback,
+        };
+        let blue_noise = match self.dither.as_ref().map(|dither| &dither.pattern) {
+            Some(DitherPattern::BlueNoise(noise)) => images.get(noise)?,
+            _ => fallback,
+        };
+        Some(vec![&palette.texture_view, &blue_noise.texture_view])
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub(crate) struct QuantizeLabel;
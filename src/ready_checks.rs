@@ -1,7 +1,12 @@
-use crate::Pixelate;
+use crate::util::{transform_aabb, union_aabb};
+use crate::{Pixelate, PixelationError, PixelationErrorReason};
+use bevy::math::Affine3A;
 use bevy::platform_support::collections::{HashMap, HashSet};
 use bevy::prelude::*;
+use bevy::render::mesh::MeshAabb as _;
+use bevy::render::primitives::Aabb;
 use bevy::scene::SceneInstance;
+use std::iter;
 
 #[derive(Debug, Resource, Reflect, Default, Deref, DerefMut)]
 #[reflect(Resource)]
@@ -22,7 +27,7 @@ pub(crate) struct PixelationTargetReadyEvent(HashMap<Entity, PixelationTarget>);
 
 #[derive(Debug, Clone)]
 pub(crate) struct PixelationTarget {
-    pub(crate) mesh_handle: Mesh3d,
+    pub(crate) aabb: Aabb,
     pub(crate) kind: PixelationTargetKind,
 }
 
@@ -41,50 +46,155 @@ pub(crate) fn get_ready_pixelation_targets(
     mesh_handles: Query<&Mesh3d>,
     meshes: Res<Assets<Mesh>>,
     scene_spawner: Res<SceneSpawner>,
+    children: Query<&Children>,
+    transforms: Query<(&Transform, Option<&ChildOf>)>,
     mut pixelation_target_ready_event: EventWriter<PixelationTargetReadyEvent>,
+    mut pixelation_errors: EventWriter<PixelationError>,
+    mut reported_errors: Local<HashSet<Entity>>,
 ) {
     let mut pixelation_targets = HashMap::default();
+    let mut failed: HashSet<Entity> = HashSet::default();
+    let mut report = |entity: Entity, reason: PixelationErrorReason| {
+        // Entities that stay queued are checked every frame, so only report each of them once.
+        if reported_errors.insert(entity) {
+            warn!("Failed to pixelate {entity}: {reason}");
+            pixelation_errors.write(PixelationError { entity, reason });
+        }
+    };
     for &entity in to_pixelate.iter() {
-        let (mesh_handle, scene_handle, scene_instance) = pixelate_query.get(entity).unwrap();
+        let Ok((mesh_handle, scene_handle, scene_instance)) = pixelate_query.get(entity) else {
+            debug!("Entity to pixelate no longer exists or is no longer marked for pixelation");
+            failed.insert(entity);
+            continue;
+        };
         if scene_handle.is_some() {
             debug!("Pixelating a scene; waiting for it to load...");
             if let Some(scene_instance) = scene_instance {
                 debug!("The scene is loaded, waiting for it to be ready...");
                 let scene_instance = **scene_instance;
                 if scene_spawner.instance_is_ready(scene_instance) {
-                    let mesh_handle = scene_spawner
-                        .iter_instance_entities(scene_instance)
-                        .filter_map(|entity| mesh_handles.get(entity).ok())
-                        .next()
-                        .unwrap();
+                    let instance_entities = scene_spawner.iter_instance_entities(scene_instance);
+                    let aabb = match get_bounds(
+                        entity,
+                        instance_entities,
+                        &mesh_handles,
+                        &meshes,
+                        &transforms,
+                    ) {
+                        Bounds::Ready(aabb) => aabb,
+                        Bounds::Loading => continue,
+                        Bounds::NoMeshes => {
+                            // The scene is done spawning, so waiting will not help.
+                            report(entity, PixelationErrorReason::SceneWithoutMesh);
+                            failed.insert(entity);
+                            continue;
+                        }
+                        Bounds::NoPositions => {
+                            report(entity, PixelationErrorReason::MeshWithoutBounds);
+                            continue;
+                        }
+                    };
 
                     debug!("The scene is ready!");
                     pixelation_targets.insert(
                         entity,
                         PixelationTarget {
-                            mesh_handle: mesh_handle.clone(),
+                            aabb,
                             kind: PixelationTargetKind::Scene,
                         },
                     );
                 }
             }
-        } else if let Some(mesh_handle) = mesh_handle {
+        } else if mesh_handle.is_some() {
             debug!("Pixelating a mesh; waiting for it to load...");
-            if meshes.contains(mesh_handle) {
-                debug!("The mesh is loaded!");
-                pixelation_targets.insert(
-                    entity,
-                    PixelationTarget {
-                        mesh_handle: mesh_handle.clone(),
-                        kind: PixelationTargetKind::Mesh,
-                    },
-                );
-            }
+            let hierarchy = iter::once(entity).chain(children.iter_descendants(entity));
+            let aabb = match get_bounds(entity, hierarchy, &mesh_handles, &meshes, &transforms) {
+                Bounds::Ready(aabb) => aabb,
+                Bounds::Loading | Bounds::NoMeshes => continue,
+                Bounds::NoPositions => {
+                    report(entity, PixelationErrorReason::MeshWithoutBounds);
+                    continue;
+                }
+            };
+            debug!("The mesh is loaded!");
+            pixelation_targets.insert(
+                entity,
+                PixelationTarget {
+                    aabb,
+                    kind: PixelationTargetKind::Mesh,
+                },
+            );
         } else {
-            panic!("The Pixelate component can only be added to entities with a Mesh or a Scene, but found neither.");
+            // The mesh or scene might still be added later, so keep the entity queued.
+            report(entity, PixelationErrorReason::MissingMeshOrScene);
         }
     }
-    let ready = pixelation_targets.keys().copied().collect();
-    to_pixelate.0 = to_pixelate.difference(&ready).copied().collect();
+    let done: HashSet<_> = pixelation_targets.keys().copied().chain(failed).collect();
+    to_pixelate.0 = to_pixelate.difference(&done).copied().collect();
+    for entity in &done {
+        reported_errors.remove(entity);
+    }
     pixelation_target_ready_event.write(PixelationTargetReadyEvent(pixelation_targets));
 }
+
+enum Bounds {
+    Ready(Aabb),
+    Loading,
+    NoMeshes,
+    NoPositions,
+}
+
+/// Computes the union of the bounds of all meshes in `entities`, in the local space of `root`.
+fn get_bounds(
+    root: Entity,
+    entities: impl Iterator<Item = Entity>,
+    mesh_handles: &Query<&Mesh3d>,
+    meshes: &Assets<Mesh>,
+    transforms: &Query<(&Transform, Option<&ChildOf>)>,
+) -> Bounds {
+    let mut found_mesh = false;
+    let mut bounds: Option<Aabb> = None;
+    for entity in entities {
+        let Ok(mesh_handle) = mesh_handles.get(entity) else {
+            continue;
+        };
+        found_mesh = true;
+        let Some(mesh) = meshes.get(mesh_handle) else {
+            return Bounds::Loading;
+        };
+        let Some(aabb) = mesh.compute_aabb() else {
+            continue;
+        };
+        let aabb = transform_aabb(&aabb, &relative_transform(root, entity, transforms));
+        bounds = Some(match bounds {
+            Some(bounds) => union_aabb(&bounds, &aabb),
+            None => aabb,
+        });
+    }
+    match bounds {
+        Some(aabb) => Bounds::Ready(aabb),
+        None if found_mesh => Bounds::NoPositions,
+        None => Bounds::NoMeshes,
+    }
+}
+
+/// Returns the transform from the local space of `entity` to the local space of its ancestor `root`.
+fn relative_transform(
+    root: Entity,
+    entity: Entity,
+    transforms: &Query<(&Transform, Option<&ChildOf>)>,
+) -> Affine3A {
+    let mut affine = Affine3A::IDENTITY;
+    let mut current = entity;
+    while current != root {
+        let Ok((transform, child_of)) = transforms.get(current) else {
+            break;
+        };
+        affine = transform.compute_affine() * affine;
+        let Some(child_of) = child_of else {
+            break;
+        };
+        current = child_of.parent;
+    }
+    affine
+}
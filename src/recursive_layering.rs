@@ -1,6 +1,8 @@
+use crate::layers::{replace_render_layers, AllocatedLayers};
 use crate::ready_checks::{PixelationTargetKind, PixelationTargetReadyEvent};
-use crate::PIXELATION_RENDER_LAYERS;
+use crate::{Pixelate, PixelationLayers};
 use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
 use bevy::scene::SceneInstance;
 
 pub(crate) fn recursively_set_layer(
@@ -10,26 +12,45 @@ pub(crate) fn recursively_set_layer(
     mesh_handles: Query<&Mesh3d>,
     scene_instances: Query<&SceneInstance>,
     scene_spawner: Res<SceneSpawner>,
+    mut allocated_layers: ResMut<AllocatedLayers>,
+    layer_config: Res<PixelationLayers>,
+    render_layers_query: Query<&RenderLayers>,
+    pixelated_query: Query<(), With<Pixelate>>,
 ) {
     for event in ready_events.read() {
         for (&entity, pixelation_target) in event.iter() {
+            if !pixelated_query.contains(entity) {
+                continue;
+            }
+            let render_layers = allocated_layers.target_layers(entity, &layer_config);
             match pixelation_target.kind {
                 PixelationTargetKind::Mesh => {
                     for child in children.iter_descendants(entity) {
                         if mesh_handles.contains(child) {
-                            commands
-                                .entity(child)
-                                .insert(PIXELATION_RENDER_LAYERS.clone());
+                            replace_render_layers(
+                                &mut commands,
+                                child,
+                                entity,
+                                render_layers_query.get(child).ok(),
+                                render_layers.clone(),
+                            );
                         }
                     }
                 }
                 PixelationTargetKind::Scene => {
-                    let scene_instance = scene_instances.get(entity).unwrap();
+                    // The target may have lost its scene since it was ready.
+                    let Ok(scene_instance) = scene_instances.get(entity) else {
+                        continue;
+                    };
                     for child in scene_spawner.iter_instance_entities(**scene_instance) {
                         if mesh_handles.contains(child) {
-                            commands
-                                .entity(child)
-                                .insert(PIXELATION_RENDER_LAYERS.clone());
+                            replace_render_layers(
+                                &mut commands,
+                                child,
+                                entity,
+                                render_layers_query.get(child).ok(),
+                                render_layers.clone(),
+                            );
                         }
                     }
                 }
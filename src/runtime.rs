@@ -1,127 +1,599 @@
-use crate::creation::{create_canvas_image, create_canvas_material};
-use crate::util::get_max_radius;
-use crate::{Canvas, Pixelate, PixelationCamera};
+use crate::bounds::MeshBounds;
+use crate::creation::{create_canvas_image, update_canvas_material, CANVAS_IMAGE_FORMAT};
+use crate::density::{
+    canvas_image_size, get_canvas_scale, get_tight_texel_size, snap_to_pixel_grid,
+};
+use crate::depth::{CanvasDepthMaterial, PixelationDepth};
+use crate::framing::{frame_rectangle, frame_sphere, frame_window, get_tight_frame};
+use crate::layers::{AllocatedLayers, ReplacedRenderLayers};
+use crate::pool::RenderTargetPool;
+use crate::shadow::{PixelationShadow, SetSceneShadow, ShadowScene};
+use crate::util::{get_max_radius, get_world_center, get_world_scale};
+use crate::{
+    Canvas, CanvasFraming, CanvasMaterial, InheritedCameraSettings, PixelDensity, Pixelate,
+    PixelationBounds, PixelationCamera, PixelationError, PixelationErrorReason,
+    PixelationProjection, PixelationRenderLayers,
+};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::ecs::system::EntityCommands;
 use bevy::platform_support::collections::HashSet;
-use bevy::render::camera::RenderTarget;
-use bevy::render::view::VisibleEntities;
-use bevy::{prelude::*, render::primitives::Aabb};
+use bevy::prelude::*;
+use bevy::render::camera::{Exposure, RenderTarget};
+use bevy::render::view::{ColorGrading, RenderLayers, VisibleEntities};
 use std::any::TypeId;
 use std::iter;
 
-/// Syncs the pixelation camera to the main camera.
+/// Syncs each pixelation camera to its main camera.
+/// Targets and main cameras are followed by their [`GlobalTransform`], so they can be anywhere in a hierarchy.
 pub(crate) fn sync_cameras<T: Component>(
     mut commands: Commands,
-    mut pixelation_camera_query: Query<(Entity, &mut Transform, &PixelationCamera), Without<T>>,
-    outer_camera_query: Query<&Transform, (With<T>, Without<PixelationCamera>)>,
-    main_object_query: Query<(&Transform, &Aabb), (Without<T>, Without<PixelationCamera>)>,
+    mut pixelation_camera_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut PixelationCamera,
+            &Camera,
+            &mut Projection,
+            Option<&PixelationDepth>,
+        ),
+        Without<T>,
+    >,
+    outer_camera_query: Query<
+        (&GlobalTransform, &Camera, &Projection),
+        (With<T>, Without<PixelationCamera>),
+    >,
+    main_object_query: Query<
+        (&GlobalTransform, &PixelationBounds, &Pixelate),
+        (Without<T>, Without<PixelationCamera>),
+    >,
+    pixelated_query: Query<(), With<Pixelate>>,
+    images: Res<Assets<Image>>,
+    mut pool: ResMut<RenderTargetPool>,
+    mut errors: EventWriter<PixelationError>,
 ) {
-    for (entity, mut pixelation_camera_transform, pixelation_camera) in &mut pixelation_camera_query
+    for (
+        entity,
+        mut pixelation_camera_transform,
+        mut pixelation_camera,
+        pixelation_render_camera,
+        mut pixelation_projection,
+        depth,
+    ) in &mut pixelation_camera_query
     {
-        for outer_camera_transform in outer_camera_query.iter() {
-            if let Ok((main_object_transform, aabb)) =
-                main_object_query.get(pixelation_camera.target)
-            {
-                *pixelation_camera_transform = outer_camera_transform.looking_at(
-                    main_object_transform.translation,
-                    *outer_camera_transform.up(),
+        let Ok((outer_camera_global_transform, camera, projection)) =
+            outer_camera_query.get(pixelation_camera.main_camera)
+        else {
+            continue;
+        };
+        if let Ok((main_object_global_transform, bounds, pixelate)) =
+            main_object_query.get(pixelation_camera.target)
+        {
+            pixelation_camera.canvas_offset = Vec3::ZERO;
+            pixelation_camera.tight_frame = None;
+            pixelation_camera.canvas_size = None;
+            if pixelate.projection == PixelationProjection::OffAxis {
+                // Needs the final canvas placement, see `sync_off_axis_cameras`.
+                continue;
+            }
+            let target_position = get_world_center(bounds, main_object_global_transform);
+            *pixelation_camera_transform = outer_camera_global_transform
+                .compute_transform()
+                .looking_at(target_position, outer_camera_global_transform.up());
+            pixelation_camera_transform.translation = target_position;
+            let back = pixelation_camera_transform.back();
+            let radius = get_max_radius(bounds) * get_world_scale(main_object_global_transform);
+            // Framing a larger sphere makes the image cover a larger area, matching a scaled up canvas.
+            let canvas_scale = get_canvas_scale(
+                pixelate,
+                bounds,
+                main_object_global_transform,
+                camera,
+                projection,
+                outer_camera_global_transform,
+            );
+            let image = get_render_image(pixelation_render_camera, &images);
+            let aspect_ratio = image.map_or(1., |image| image.aspect_ratio().ratio());
+            let mut framing = frame_sphere(
+                pixelate.projection,
+                radius * canvas_scale,
+                aspect_ratio,
+                &mut pixelation_projection,
+            );
+            pixelation_camera_transform.translation += back * framing.distance;
+
+            if pixelate.framing == CanvasFraming::Tight {
+                let frame = get_tight_frame(
+                    pixelate.projection,
+                    bounds,
+                    main_object_global_transform,
+                    &pixelation_camera_transform,
                 );
-                pixelation_camera_transform.translation = main_object_transform.translation;
-                let back = pixelation_camera_transform.back();
-                let radius = get_max_radius(aabb);
-
-                // Chosen by eye, feel free to explain to me why this works :)
-                const DISTANCE_FACTOR: f32 = 3.2;
-                pixelation_camera_transform.translation += back * radius * DISTANCE_FACTOR;
-            } else {
-                debug!("Despawning pixelation camera because it holds an invalid target.");
-                commands.entity(entity).despawn();
-                if let Ok(mut entity_commands) = commands.get_entity(pixelation_camera.target) {
-                    entity_commands.despawn();
+                pixelation_camera.tight_frame = Some(frame);
+                if let Some(image) = image {
+                    // Grow the frame to the aspect ratio of the image, so that texels stay square.
+                    let texel_size = get_tight_texel_size(
+                        pixelate,
+                        bounds,
+                        frame,
+                        image.size(),
+                        main_object_global_transform,
+                        camera,
+                        projection,
+                        outer_camera_global_transform,
+                    );
+                    let canvas_size = image.size().as_vec2() * texel_size;
+                    framing =
+                        frame_rectangle(framing.distance, canvas_size, &mut pixelation_projection);
+                    pixelation_camera.canvas_size = Some(canvas_size);
                 }
             }
+
+            if let (true, Some(image)) = (pixelate.texel_snapping, image) {
+                // Move the camera in whole texels within its view plane, so the image does not crawl.
+                let texel_size = framing.frame_height / image.height() as f32;
+                let position = pixelation_camera_transform.translation;
+                let snap = |axis: Dir3| {
+                    let coordinate = position.dot(*axis);
+                    ((coordinate / texel_size).round() * texel_size - coordinate) * axis
+                };
+                let shift = snap(pixelation_camera_transform.right())
+                    + snap(pixelation_camera_transform.up());
+                pixelation_camera_transform.translation += shift;
+
+                // The image now shows the target shifted by a fraction of a texel; move the canvas to undo that.
+                let canvas_height = pixelation_camera
+                    .canvas_size
+                    .map_or(2. * radius * canvas_scale, |canvas_size| canvas_size.y);
+                pixelation_camera.canvas_offset = shift * canvas_height / framing.frame_height;
+            }
+        } else {
+            debug!("Despawning pixelation camera because it holds an invalid target.");
+            pool.release_camera_textures(pixelation_render_camera, depth);
+            commands.entity(entity).despawn();
+            // Targets that are gone or no longer pixelated are torn down by `despawn_dependent_types`.
+            if pixelated_query.contains(pixelation_camera.target) {
+                let error = PixelationError {
+                    entity: pixelation_camera.target,
+                    reason: PixelationErrorReason::InvalidTarget,
+                };
+                warn!("Failed to pixelate {}: {}", error.entity, error.reason);
+                errors.write(error);
+            }
         }
     }
 }
 
-/// Rotates the canvas (main pass)
+/// Rotates each canvas (main pass) to face its main camera.
+/// Canvases are not parented to their target, so they are placed in world space.
 pub(crate) fn position_canvas<T: Component>(
     mut commands: Commands,
     mut canvas_query: Query<(Entity, &mut Transform, &Canvas), Without<T>>,
-    outer_camera_query: Query<&Transform, (With<T>, Without<Canvas>)>,
-    main_object_query: Query<(&Transform, &Aabb), (Without<T>, Without<Canvas>)>,
+    outer_camera_query: Query<(&GlobalTransform, &Camera, &Projection), (With<T>, Without<Canvas>)>,
+    main_object_query: Query<
+        (&GlobalTransform, &PixelationBounds, &Pixelate),
+        (Without<T>, Without<Canvas>),
+    >,
+    images: Res<Assets<Image>>,
+    pixelation_camera_query: Query<(&PixelationCamera, &Camera), Without<T>>,
 ) {
     for (entity, mut canvas_transform, canvas) in &mut canvas_query {
-        if let Ok((main_object_transform, aabb)) = main_object_query.get(canvas.target) {
-            for camera_transform in outer_camera_query.iter() {
-                *canvas_transform = main_object_transform
-                    .looking_at(camera_transform.translation, *camera_transform.up());
-                let forward = canvas_transform.forward();
-                let radius = get_max_radius(aabb);
-                canvas_transform.translation += forward * radius;
+        if let Ok((main_object_global_transform, bounds, pixelate)) =
+            main_object_query.get(canvas.target)
+        {
+            let Ok((camera_global_transform, camera, projection)) =
+                outer_camera_query.get(canvas.main_camera)
+            else {
+                continue;
+            };
+            let pixelation_camera = pixelation_camera_query.get(canvas.pixelation_camera).ok();
+            // Set by `sync_cameras` for tight framing.
+            let canvas_size =
+                pixelation_camera.and_then(|(pixelation_camera, _)| pixelation_camera.canvas_size);
+
+            // The canvas mesh is a unit quad, so the canvas is scaled to the size of the target every frame.
+            let radius = get_max_radius(bounds) * get_world_scale(main_object_global_transform);
+            let scale = canvas_size.map_or(Vec3::splat(radius), |canvas_size| {
+                (canvas_size / 2.).extend(radius)
+            });
+            *canvas_transform =
+                Transform::from_translation(get_world_center(bounds, main_object_global_transform))
+                    .looking_at(
+                        camera_global_transform.translation(),
+                        camera_global_transform.up(),
+                    )
+                    .with_scale(scale);
+            let forward = canvas_transform.forward();
+            canvas_transform.translation += forward * radius;
+
+            if let Some((pixelation_camera, _)) = pixelation_camera {
+                canvas_transform.translation += pixelation_camera.canvas_offset;
+            }
+
+            if let PixelDensity::PixelPerfect {
+                screen_pixels_per_pixel,
+            } = pixelate.density
+            {
+                // Tightly framed canvases already have the exact size.
+                if canvas_size.is_none() {
+                    canvas_transform.scale *= get_canvas_scale(
+                        pixelate,
+                        bounds,
+                        main_object_global_transform,
+                        camera,
+                        projection,
+                        camera_global_transform,
+                    );
+                }
+                let image_size = pixelation_camera
+                    .and_then(|(_, camera)| get_render_image(camera, &images))
+                    .map(|image| image.size());
+                if let Some(snapped) = image_size.and_then(|image_size| {
+                    snap_to_pixel_grid(
+                        canvas_transform.translation,
+                        image_size * screen_pixels_per_pixel.max(1),
+                        camera,
+                        camera_global_transform,
+                    )
+                }) {
+                    canvas_transform.translation = snapped;
+                }
             }
         } else {
+            // The error is reported by `sync_cameras`.
             debug!("Despawning canvas because it holds an invalid target.");
             commands.entity(entity).despawn();
-            if let Ok(mut entity_commands) = commands.get_entity(canvas.target) {
-                entity_commands.despawn();
-            }
         }
     }
 }
 
+/// Places the pixelation cameras of targets using [`PixelationProjection::OffAxis`] at their main camera,
+/// looking through their canvas.
+pub(crate) fn sync_off_axis_cameras<T: Component>(
+    mut pixelation_camera_query: Query<
+        (&mut Transform, &PixelationCamera, &mut Projection),
+        (Without<T>, Without<Canvas>),
+    >,
+    outer_camera_query: Query<&GlobalTransform, With<T>>,
+    main_object_query: Query<&Pixelate>,
+    canvas_query: Query<&Transform, (With<Canvas>, Without<PixelationCamera>)>,
+) {
+    for (mut pixelation_camera_transform, pixelation_camera, mut projection) in
+        &mut pixelation_camera_query
+    {
+        let Ok(pixelate) = main_object_query.get(pixelation_camera.target) else {
+            continue;
+        };
+        if pixelate.projection != PixelationProjection::OffAxis {
+            continue;
+        }
+        let Ok(outer_camera_transform) = outer_camera_query.get(pixelation_camera.main_camera)
+        else {
+            continue;
+        };
+        let Ok(canvas_transform) = canvas_query.get(pixelation_camera.canvas) else {
+            continue;
+        };
+        // The canvas mesh is turned around, so its image runs along the canvas' left.
+        frame_window(
+            outer_camera_transform.translation(),
+            canvas_transform.translation,
+            *canvas_transform.left(),
+            *canvas_transform.up(),
+            canvas_transform.scale.truncate(),
+            &mut pixelation_camera_transform,
+            &mut projection,
+        );
+    }
+}
+
+/// The pixelation systems run after transform propagation, so that they see where targets and main cameras are this frame.
+/// The canvases and pixelation cameras they move are not parented to anything, so their global transforms are updated here.
+pub(crate) fn propagate_pixelation_transforms(
+    mut root_query: Query<
+        (&Transform, &mut GlobalTransform, Option<&Children>),
+        Or<(With<Canvas>, With<PixelationCamera>)>,
+    >,
+    mut child_query: Query<
+        (&Transform, &mut GlobalTransform),
+        (Without<Canvas>, Without<PixelationCamera>),
+    >,
+) {
+    for (transform, mut global_transform, children) in &mut root_query {
+        *global_transform = GlobalTransform::from(*transform);
+        let mut children = child_query.iter_many_mut(children.into_iter().flatten());
+        while let Some((child_transform, mut child_global_transform)) = children.fetch_next() {
+            *child_global_transform = global_transform.mul_transform(*child_transform);
+        }
+    }
+}
+
+/// Copies the camera components allowed by [`InheritedCameraSettings`] from each main camera to its pixelation cameras,
+/// so that pixelated targets are lit and exposed like the rest of the world.
+pub(crate) fn sync_camera_settings<T: Component>(
+    mut commands: Commands,
+    settings: Res<InheritedCameraSettings>,
+    pixelation_camera_query: Query<
+        (
+            Entity,
+            Ref<PixelationCamera>,
+            Has<EnvironmentMapLight>,
+            Has<AmbientLight>,
+            Has<Tonemapping>,
+            Has<Exposure>,
+            Has<DistanceFog>,
+            Has<ColorGrading>,
+        ),
+        Without<T>,
+    >,
+    main_camera_query: Query<
+        (
+            Option<Ref<EnvironmentMapLight>>,
+            Option<Ref<AmbientLight>>,
+            Option<Ref<Tonemapping>>,
+            Option<Ref<Exposure>>,
+            Option<Ref<DistanceFog>>,
+            Option<Ref<ColorGrading>>,
+        ),
+        (With<T>, Without<PixelationCamera>),
+    >,
+) {
+    for (
+        entity,
+        pixelation_camera,
+        has_environment_map,
+        has_ambient_light,
+        has_tonemapping,
+        has_exposure,
+        has_fog,
+        has_color_grading,
+    ) in &pixelation_camera_query
+    {
+        let Ok((environment_map, ambient_light, tonemapping, exposure, fog, color_grading)) =
+            main_camera_query.get(pixelation_camera.main_camera)
+        else {
+            continue;
+        };
+        let force = settings.is_changed() || pixelation_camera.is_added();
+        let mut entity_commands = commands.entity(entity);
+        let pixelation_camera = &mut entity_commands;
+        inherit(
+            pixelation_camera,
+            settings.environment_map,
+            environment_map,
+            has_environment_map,
+            force,
+        );
+        inherit(
+            pixelation_camera,
+            settings.ambient_light,
+            ambient_light,
+            has_ambient_light,
+            force,
+        );
+        inherit(
+            pixelation_camera,
+            settings.tonemapping,
+            tonemapping,
+            has_tonemapping,
+            force,
+        );
+        inherit(
+            pixelation_camera,
+            settings.exposure,
+            exposure,
+            has_exposure,
+            force,
+        );
+        inherit(pixelation_camera, settings.fog, fog, has_fog, force);
+        inherit(
+            pixelation_camera,
+            settings.color_grading,
+            color_grading,
+            has_color_grading,
+            force,
+        );
+    }
+}
+
+/// Copies `main_camera_component` to the pixelation camera if it changed, or removes it if the main camera has none.
+fn inherit<C: Component + Clone>(
+    pixelation_camera: &mut EntityCommands,
+    enabled: bool,
+    main_camera_component: Option<Ref<C>>,
+    pixelation_camera_has_component: bool,
+    force: bool,
+) {
+    if !enabled {
+        return;
+    }
+    match main_camera_component {
+        Some(component) if force || component.is_changed() || !pixelation_camera_has_component => {
+            pixelation_camera.insert(component.clone());
+        }
+        None if pixelation_camera_has_component => {
+            pixelation_camera.remove::<C>();
+        }
+        _ => {}
+    }
+}
+
+fn get_render_image<'a>(camera: &Camera, images: &'a Assets<Image>) -> Option<&'a Image> {
+    match &camera.target {
+        RenderTarget::Image(image_target) => images.get(&image_target.handle),
+        _ => None,
+    }
+}
+
+/// Tears down everything the plugin added for targets whose [`Pixelate`] was removed,
+/// so that they look like they did before and can be pixelated again later.
 pub(crate) fn despawn_dependent_types(
     mut commands: Commands,
     mut removed_pixelate: RemovedComponents<Pixelate>,
-    canvas_query: Query<Entity, With<Canvas>>,
-    pixelation_camera_query: Query<Entity, With<PixelationCamera>>,
+    mut allocated_layers: ResMut<AllocatedLayers>,
+    mut pool: ResMut<RenderTargetPool>,
+    mut scene_spawner: ResMut<SceneSpawner>,
+    mut set_scene_shadow: ResMut<SetSceneShadow>,
+    canvas_query: Query<(Entity, &Canvas)>,
+    pixelation_camera_query: Query<(Entity, &PixelationCamera, &Camera, Option<&PixelationDepth>)>,
+    replaced_layers_query: Query<(Entity, &ReplacedRenderLayers)>,
+    target_query: Query<(Option<&Children>, Option<&ShadowScene>)>,
+    shadow_query: Query<(), With<PixelationShadow>>,
 ) {
     for entity in removed_pixelate.read() {
         debug!("Pixelate was removed from an entity; removing canvas and pixelation camera that held it as target.");
-        for canvas in canvas_query.iter() {
-            if canvas == entity {
-                commands.entity(canvas).despawn();
+        allocated_layers.release(entity);
+        for (canvas_entity, canvas) in &canvas_query {
+            if canvas.target == entity {
+                commands.entity(canvas_entity).despawn();
+            }
+        }
+        for (camera_entity, pixelation_camera, camera, depth) in &pixelation_camera_query {
+            if pixelation_camera.target == entity {
+                pool.release_camera_textures(camera, depth);
+                commands.entity(camera_entity).despawn();
             }
         }
-        for pixelation_camera in pixelation_camera_query.iter() {
-            if pixelation_camera == entity {
-                commands.entity(pixelation_camera).despawn();
+        for (layered_entity, replaced_layers) in &replaced_layers_query {
+            if replaced_layers.target != entity {
+                continue;
+            }
+            let mut layered_entity = commands.entity(layered_entity);
+            layered_entity.try_remove::<ReplacedRenderLayers>();
+            match &replaced_layers.original {
+                Some(original) => layered_entity.try_insert(original.clone()),
+                None => layered_entity.try_remove::<RenderLayers>(),
+            };
+        }
+
+        // The target itself may have been despawned.
+        let Ok((children, shadow_scene)) = target_query.get(entity) else {
+            continue;
+        };
+        commands.entity(entity).try_remove::<(
+            PixelationBounds,
+            MeshBounds,
+            PixelationRenderLayers,
+            ShadowScene,
+        )>();
+        match shadow_scene {
+            Some(shadow_scene) => {
+                set_scene_shadow.remove(&**shadow_scene);
+                scene_spawner.despawn_instance(**shadow_scene);
+            }
+            None => {
+                for &child in children.into_iter().flatten() {
+                    if shadow_query.contains(child) {
+                        commands.entity(child).despawn();
+                    }
+                }
             }
         }
     }
 }
 
-pub(crate) fn update_pixelation(
+/// Swaps the render texture of every pixelation camera whose desired size changed for one from the [`RenderTargetPool`],
+/// and updates the canvas material when the texture or the target's [`Pixelate`] changed.
+pub(crate) fn update_pixelation<C: Component, M: CanvasMaterial>(
     mut commands: Commands,
-    pixelate_query: Query<(Entity, &Pixelate), Changed<Pixelate>>,
-    mut pixelation_camera_query: Query<(&PixelationCamera, &mut Camera)>,
-    canvas_query: Query<(&Canvas, &Children)>,
-    with_standard_material: Query<Entity, With<MeshMaterial3d<StandardMaterial>>>,
-    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut pixelation_camera_query: Query<(
+        Entity,
+        &PixelationCamera,
+        &mut Camera,
+        Option<&PixelationDepth>,
+    )>,
+    main_camera_query: Query<
+        (&Camera, &Projection, &GlobalTransform),
+        (With<C>, Without<PixelationCamera>),
+    >,
+    target_query: Query<(Ref<Pixelate>, &PixelationBounds, &GlobalTransform)>,
+    canvas_query: Query<&Children, With<Canvas>>,
+    canvas_mesh_query: Query<
+        (
+            Option<&MeshMaterial3d<M>>,
+            Option<&MeshMaterial3d<CanvasDepthMaterial>>,
+        ),
+        With<Mesh3d>,
+    >,
+    mut materials: ResMut<Assets<M>>,
+    mut depth_materials: ResMut<Assets<CanvasDepthMaterial>>,
     mut images: ResMut<Assets<Image>>,
+    mut pool: ResMut<RenderTargetPool>,
 ) {
-    for (entity, pixelate) in pixelate_query.iter() {
-        if let Some((_, mut camera)) = pixelation_camera_query
-            .iter_mut()
-            .find(|(pixelation_camera, _)| pixelation_camera.target == entity)
-        {
-            if let Some((_, children)) = canvas_query
-                .iter()
-                .find(|(canvas, _)| canvas.target == entity)
-            {
-                if let Some(entity) = children
-                    .iter()
-                    .find(|entity| with_standard_material.contains(*entity))
-                {
-                    let image_handle = images.add(create_canvas_image(*pixelate));
-                    camera.target = RenderTarget::Image(image_handle.clone().into());
-                    let material_handle =
-                        standard_materials.add(create_canvas_material(image_handle));
-                    commands
-                        .entity(entity)
-                        .insert(MeshMaterial3d(material_handle));
-                }
+    for (entity, pixelation_camera, mut camera, current_depth) in pixelation_camera_query.iter_mut()
+    {
+        // Pixelation cameras of other main camera components belong to another instance of the plugin.
+        let Ok(main_camera) = main_camera_query.get(pixelation_camera.main_camera) else {
+            continue;
+        };
+        let Ok((pixelate, bounds, target_transform)) = target_query.get(pixelation_camera.target)
+        else {
+            continue;
+        };
+        let size = canvas_image_size(
+            &pixelate,
+            bounds,
+            target_transform,
+            Some(main_camera),
+            pixelation_camera.tight_frame,
+        );
+        let current_image = match &camera.target {
+            RenderTarget::Image(image_target) => images
+                .get(&image_target.handle)
+                .filter(|image| image.size() == size)
+                .map(|_| image_target.handle.clone()),
+            _ => None,
+        };
+        if current_image.is_some() && !pixelate.is_changed() {
+            continue;
+        }
+        let Some(canvas_mesh) =
+            canvas_query
+                .get(pixelation_camera.canvas)
+                .ok()
+                .and_then(|children| {
+                    children
+                        .iter()
+                        .find(|entity| canvas_mesh_query.contains(*entity))
+                })
+        else {
+            continue;
+        };
+        let Ok((current_material, current_depth_material)) = canvas_mesh_query.get(canvas_mesh)
+        else {
+            continue;
+        };
+        let image_handle = current_image.unwrap_or_else(|| {
+            if let RenderTarget::Image(image_target) = &camera.target {
+                pool.release(&image_target.handle);
             }
+            let image_handle =
+                pool.acquire(&mut images, size, CANVAS_IMAGE_FORMAT, create_canvas_image);
+            camera.target = RenderTarget::Image(image_handle.clone().into());
+            image_handle
+        });
+        if let Some(current_depth) = current_depth {
+            pool.release(&current_depth.texture);
         }
+        let depth = update_canvas_material(
+            &mut commands.entity(canvas_mesh),
+            current_material,
+            current_depth_material,
+            pixelation_camera.target,
+            image_handle,
+            size,
+            &pixelate,
+            &mut images,
+            &mut pool,
+            &mut materials,
+            &mut depth_materials,
+        );
+        let mut pixelation_camera = commands.entity(entity);
+        match depth {
+            Some(depth) => pixelation_camera.insert(depth),
+            None => pixelation_camera
+                .remove::<PixelationDepth>()
+                .insert(Msaa::default()),
+        };
     }
 }
 
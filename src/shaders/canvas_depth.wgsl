@@ -0,0 +1,38 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+@group(2) @binding(0) var<uniform> world_from_pixelation_clip: mat4x4<f32>;
+@group(2) @binding(1) var color_texture: texture_2d<f32>;
+@group(2) @binding(2) var color_sampler: sampler;
+@group(2) @binding(3) var depth_texture: texture_depth_2d;
+@group(2) @binding(4) var<uniform> alpha_cutoff: f32;
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) depth: f32,
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> FragmentOutput {
+    let color = textureSample(color_texture, color_sampler, in.uv);
+    if color.a < alpha_cutoff {
+        discard;
+    }
+
+    // Every texel gets the depth of its center, so pixels stay blocky where they intersect the world.
+    let size = textureDimensions(depth_texture);
+    let texel = min(vec2<u32>(in.uv * vec2<f32>(size)), size - 1u);
+    let depth = textureLoad(depth_texture, texel, 0);
+    let uv = (vec2<f32>(texel) + 0.5) / vec2<f32>(size);
+    let ndc = vec3(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth);
+
+    let world = world_from_pixelation_clip * vec4(ndc, 1.0);
+    let clip = view.clip_from_world * vec4(world.xyz / world.w, 1.0);
+
+    var out: FragmentOutput;
+    out.color = color;
+    out.depth = clip.z / clip.w;
+    return out;
+}
@@ -0,0 +1,41 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct LightingRampSettings {
+    bands: f32,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> settings: LightingRampSettings;
+@group(0) @binding(2) var ramp: texture_2d<f32>;
+
+fn to_srgb(value: f32) -> f32 {
+    return select(1.055 * pow(value, 1.0 / 2.4) - 0.055, value * 12.92, value <= 0.0031308);
+}
+
+fn to_linear(value: f32) -> f32 {
+    return select(pow((value + 0.055) / 1.055, 2.4), value / 12.92, value <= 0.04045);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureLoad(source, vec2<i32>(in.position.xy), 0);
+    let luminance = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    if luminance <= 0.0 {
+        return color;
+    }
+    // The brightness as it is perceived, so that bands are evenly spaced to the eye.
+    let brightness = clamp(to_srgb(luminance), 0.0, 1.0);
+    // The color at a luminance of 1.
+    let hue = color.rgb / luminance;
+
+#ifdef RAMP_TEXTURE
+    let width = textureDimensions(ramp).x;
+    let x = min(u32(brightness * f32(width)), width - 1u);
+    // An sRGB ramp texture is converted to linear when loaded, so a gradient from black to white maps every luminance to itself.
+    return vec4(hue * textureLoad(ramp, vec2(x, 0u), 0).rgb, color.a);
+#else
+    let band = floor(min(brightness, 0.9999) * settings.bands);
+    let banded = to_linear((band + 0.5) / settings.bands);
+    return vec4(hue * banded, color.a);
+#endif
+}
@@ -0,0 +1,128 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct OutlineSettings {
+    color: vec4<f32>,
+    width: f32,
+    threshold: f32,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> settings: OutlineSettings;
+
+#ifdef DEPTH_EDGES
+#ifdef MULTISAMPLED
+@group(0) @binding(2) var prepass_depth: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(2) var prepass_depth: texture_depth_2d;
+#endif
+#endif
+
+#ifdef NORMAL_EDGES
+#ifdef MULTISAMPLED
+@group(0) @binding(2) var prepass_normal: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(2) var prepass_normal: texture_2d<f32>;
+#endif
+#endif
+
+// Texels outside of the image count as transparent.
+fn is_opaque(coords: vec2<i32>) -> bool {
+    let size = vec2<i32>(textureDimensions(source));
+    if any(coords < vec2(0)) || any(coords >= size) {
+        return false;
+    }
+    return textureLoad(source, coords, 0).a >= 0.5;
+}
+
+// Whether a texel on the other side of the silhouette lies within `width` texels,
+// measured along the axes so that one texel wide outlines have no corners.
+fn near_silhouette(coords: vec2<i32>, width: i32) -> bool {
+#ifdef INNER
+    let inside = true;
+#else
+    let inside = false;
+#endif
+    for (var y = -width; y <= width; y += 1) {
+        let span = width - abs(y);
+        for (var x = -span; x <= span; x += 1) {
+            if is_opaque(coords + vec2(x, y)) != inside {
+                return true;
+            }
+        }
+    }
+    return false;
+}
+
+#ifdef DEPTH_EDGES
+// Marks texels with a neighbor that is farther away, so the line is drawn on the part in front.
+fn is_inner_edge(coords: vec2<i32>) -> bool {
+    let depth = textureLoad(prepass_depth, coords, 0);
+    var offsets = array(vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1));
+    for (var i = 0; i < 4; i += 1) {
+        let neighbor = coords + offsets[i];
+        // The edges of the silhouette are left to the outline.
+        if !is_opaque(neighbor) {
+            continue;
+        }
+        // With reverse z, a lower depth is farther away.
+        if depth - textureLoad(prepass_depth, neighbor, 0) > settings.threshold * depth {
+            return true;
+        }
+    }
+    return false;
+}
+#endif
+
+#ifdef NORMAL_EDGES
+fn load_normal(coords: vec2<i32>) -> vec3<f32> {
+    return normalize(textureLoad(prepass_normal, coords, 0).xyz * 2.0 - 1.0);
+}
+
+// Only compares with the neighbors to the right and below, so the line is one texel wide.
+fn is_inner_edge(coords: vec2<i32>) -> bool {
+    let normal = load_normal(coords);
+    var offsets = array(vec2(1, 0), vec2(0, 1));
+    for (var i = 0; i < 2; i += 1) {
+        let neighbor = coords + offsets[i];
+        if !is_opaque(neighbor) {
+            continue;
+        }
+        if 1.0 - dot(normal, load_normal(neighbor)) > settings.threshold {
+            return true;
+        }
+    }
+    return false;
+}
+#endif
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let coords = vec2<i32>(in.position.xy);
+    let color = textureLoad(source, coords, 0);
+    let opaque = color.a >= 0.5;
+    let blended = vec4(mix(color.rgb, settings.color.rgb, settings.color.a), color.a);
+    let width = i32(settings.width);
+
+#ifdef INNER
+    if opaque && width > 0 && near_silhouette(coords, width) {
+        return blended;
+    }
+#else
+    if !opaque && width > 0 && near_silhouette(coords, width) {
+        return settings.color;
+    }
+#endif
+
+#ifdef DEPTH_EDGES
+    if opaque && is_inner_edge(coords) {
+        return blended;
+    }
+#endif
+#ifdef NORMAL_EDGES
+    if opaque && is_inner_edge(coords) {
+        return blended;
+    }
+#endif
+
+    return color;
+}
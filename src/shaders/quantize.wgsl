@@ -0,0 +1,104 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct QuantizeSettings {
+    spread: f32,
+    levels: f32,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> settings: QuantizeSettings;
+@group(0) @binding(2) var palette: texture_2d<f32>;
+@group(0) @binding(3) var blue_noise: texture_2d<f32>;
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    return select(
+        1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055,
+        color * 12.92,
+        color <= vec3(0.0031308),
+    );
+}
+
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    return select(
+        pow((color + 0.055) / 1.055, vec3(2.4)),
+        color / 12.92,
+        color <= vec3(0.04045),
+    );
+}
+
+// Converts linear sRGB to Oklab, in which distances match perceived color differences.
+fn oklab(color: vec3<f32>) -> vec3<f32> {
+    let lms = mat3x3<f32>(
+        0.4122214708, 0.2119034982, 0.0883024619,
+        0.5363325363, 0.6806995451, 0.2817188376,
+        0.0514459929, 0.1073969566, 0.6299787005,
+    ) * color;
+    let lms_cbrt = sign(lms) * pow(abs(lms), vec3(1.0 / 3.0));
+    return mat3x3<f32>(
+        0.2104542553, 1.9779984951, 0.0259040371,
+        0.7936177850, -2.4285922050, 0.7827717662,
+        -0.0040720468, 0.4505937099, -0.8086757660,
+    ) * lms_cbrt;
+}
+
+fn nearest_palette_color(color: vec3<f32>) -> vec3<f32> {
+    let target_color = oklab(color);
+    var nearest = color;
+    var nearest_distance = 3.40282347e38;
+    let count = textureDimensions(palette).x;
+    for (var i = 0u; i < count; i++) {
+        let candidate = textureLoad(palette, vec2(i, 0u), 0).rgb;
+        let difference = oklab(candidate) - target_color;
+        let distance = dot(difference, difference);
+        if distance < nearest_distance {
+            nearest = candidate;
+            nearest_distance = distance;
+        }
+    }
+    return nearest;
+}
+
+#ifdef BAYER_SIZE
+// Returns the threshold of the texel in an ordered dither matrix of size `BAYER_SIZE`, in (0, 1).
+fn bayer(texel: vec2<u32>) -> f32 {
+    let x = texel.x % #{BAYER_SIZE};
+    let y = texel.y % #{BAYER_SIZE};
+    var value = 0u;
+    var weight = #{BAYER_SIZE} * #{BAYER_SIZE} / 4u;
+    for (var bit = 1u; bit < #{BAYER_SIZE}; bit <<= 1u) {
+        let xor_bit = select(0u, 2u, ((x ^ y) & bit) != 0u);
+        let y_bit = select(0u, 1u, (y & bit) != 0u);
+        value += (xor_bit + y_bit) * weight;
+        weight /= 4u;
+    }
+    return (f32(value) + 0.5) / f32(#{BAYER_SIZE} * #{BAYER_SIZE});
+}
+#endif
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // The pattern is indexed by texel, so it stays anchored to the pixels of the image.
+    let texel = vec2<u32>(in.position.xy);
+    let color = textureLoad(source, texel, 0);
+    if color.a == 0.0 {
+        return color;
+    }
+    // Dithering works on perceived brightness, so offset the colors in sRGB.
+    var srgb = to_srgb(color.rgb);
+
+#ifdef BAYER_SIZE
+    srgb += (bayer(texel) - 0.5) * settings.spread;
+#endif
+#ifdef BLUE_NOISE
+    let noise = textureLoad(blue_noise, texel % textureDimensions(blue_noise), 0).r;
+    srgb += (noise - 0.5) * settings.spread;
+#endif
+
+#ifdef PALETTE
+    let quantized = nearest_palette_color(to_linear(clamp(srgb, vec3(0.0), vec3(1.0))));
+#else
+    let steps = settings.levels - 1.0;
+    let quantized = to_linear(clamp(round(srgb * steps) / steps, vec3(0.0), vec3(1.0)));
+#endif
+    return vec4(quantized, color.a);
+}
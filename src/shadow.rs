@@ -1,14 +1,24 @@
 use crate::ready_checks::{PixelationTargetKind, PixelationTargetReadyEvent};
+use crate::Pixelate;
 use bevy::pbr::NotShadowReceiver;
-use bevy::platform_support::collections::HashSet;
+use bevy::platform_support::collections::HashMap;
 use bevy::prelude::*;
 use bevy::scene::InstanceId;
 
+/// Marks the duplicated hierarchy that only exists to cast the shadow of a pixelated target.
+#[derive(Debug, Component, Copy, Clone)]
+pub(crate) struct PixelationShadow;
+
+/// Placed on scene targets, holding the instance of the scene that casts their shadow.
+#[derive(Debug, Component, Copy, Clone, Deref)]
+pub(crate) struct ShadowScene(pub(crate) InstanceId);
+
 #[derive(Debug, Clone, Resource, Deref, DerefMut)]
 pub(crate) struct ShadowMaterialHandle(Handle<StandardMaterial>);
 
+/// The shadow scene instances that still need the shadow material once they are spawned, with their target.
 #[derive(Debug, Clone, Resource, Deref, DerefMut, Default)]
-pub(crate) struct SetSceneShadow(HashSet<InstanceId>);
+pub(crate) struct SetSceneShadow(HashMap<InstanceId, Entity>);
 
 pub(crate) fn create_shadow_material(
     mut commands: Commands,
@@ -32,15 +42,21 @@ pub(crate) fn add_shadow_caster(
     mut scene_spawner: ResMut<SceneSpawner>,
     shadow_material_handle: Res<ShadowMaterialHandle>,
     mut set_scene_shadow: ResMut<SetSceneShadow>,
+    pixelated_query: Query<(), With<Pixelate>>,
 ) {
     for event in ready_event.read() {
         for (&entity, target) in event.iter() {
+            // The event may be read a frame late, after the target was despawned or lost `Pixelate`.
+            if !pixelated_query.contains(entity) {
+                continue;
+            }
             match target.kind {
                 PixelationTargetKind::Mesh => {
                     commands.entity(entity).with_children(|parent| {
                         parent
                             .spawn((
                                 Name::new("Pixelation Shadow"),
+                                PixelationShadow,
                                 Transform::default(),
                                 Visibility::default(),
                             ))
@@ -56,9 +72,12 @@ pub(crate) fn add_shadow_caster(
                     });
                 }
                 PixelationTargetKind::Scene => {
-                    let scene_handle = scene_handles.get(entity).unwrap();
+                    let Ok(scene_handle) = scene_handles.get(entity) else {
+                        continue;
+                    };
                     let instance_id = scene_spawner.spawn_as_child(scene_handle.0.clone(), entity);
-                    set_scene_shadow.insert(instance_id);
+                    set_scene_shadow.insert(instance_id, entity);
+                    commands.entity(entity).try_insert(ShadowScene(instance_id));
                 }
             }
         }
@@ -101,25 +120,108 @@ fn duplicate_children(
 pub(crate) fn set_scene_shadow(
     mut commands: Commands,
     mut set_scene_shadow: ResMut<SetSceneShadow>,
-    scene_spawner: Res<SceneSpawner>,
+    mut scene_spawner: ResMut<SceneSpawner>,
     shadow_material_handle: Res<ShadowMaterialHandle>,
     mesh_query: Query<&Mesh3d>,
+    pixelated_query: Query<(), With<Pixelate>>,
 ) {
-    let mut done = HashSet::default();
-    for instance_id in set_scene_shadow.iter() {
-        if scene_spawner.instance_is_ready(*instance_id) {
-            done.insert(*instance_id);
+    let mut done = Vec::new();
+    set_scene_shadow.retain(|&instance_id, &mut target| {
+        // Targets that are despawned or lose `Pixelate` before getting their `ShadowScene` are not torn down otherwise.
+        if !pixelated_query.contains(target) {
+            scene_spawner.despawn_instance(instance_id);
+            return false;
         }
-    }
-    set_scene_shadow.0 = set_scene_shadow.0.difference(&done).copied().collect();
+        if scene_spawner.instance_is_ready(instance_id) {
+            done.push(instance_id);
+            return false;
+        }
+        true
+    });
     for instance_id in done {
         for entity in scene_spawner.iter_instance_entities(instance_id) {
             if mesh_query.contains(entity) {
-                commands.entity(entity).insert((
+                commands.entity(entity).try_insert((
                     NotShadowReceiver,
                     MeshMaterial3d(shadow_material_handle.0.clone()),
                 ));
             }
+            commands.entity(entity).try_insert(PixelationShadow);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ready_checks::PixelationTarget;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::render::primitives::Aabb;
+
+    #[test]
+    fn targets_that_lost_pixelate_get_no_shadow() {
+        let mut world = World::new();
+        world.init_resource::<Events<PixelationTargetReadyEvent>>();
+        world.init_resource::<SceneSpawner>();
+        world.init_resource::<SetSceneShadow>();
+        world.insert_resource(ShadowMaterialHandle(Handle::default()));
+        let kept = world
+            .spawn((Pixelate::default(), Transform::default()))
+            .id();
+        let removed = world
+            .spawn((Pixelate::default(), Transform::default()))
+            .id();
+        let mut event = PixelationTargetReadyEvent::default();
+        for entity in [kept, removed] {
+            event.insert(
+                entity,
+                PixelationTarget {
+                    aabb: Aabb::default(),
+                    kind: PixelationTargetKind::Mesh,
+                },
+            );
+        }
+        world.send_event(event);
+
+        // `Pixelate` is removed in the frame the event is sent, before `add_shadow_caster` reads it.
+        world.entity_mut(removed).remove::<Pixelate>();
+        world.run_system_once(add_shadow_caster).unwrap();
+
+        let shadow_parents: Vec<_> = world
+            .query_filtered::<&ChildOf, With<PixelationShadow>>()
+            .iter(&world)
+            .map(|child_of| child_of.parent)
+            .collect();
+        assert_eq!(shadow_parents, [kept]);
+    }
+
+    #[test]
+    fn shadow_scenes_of_invalid_targets_are_forgotten() {
+        let mut world = World::new();
+        world.init_resource::<SceneSpawner>();
+        world.init_resource::<SetSceneShadow>();
+        world.insert_resource(ShadowMaterialHandle(Handle::default()));
+        let kept = world.spawn(Pixelate::default()).id();
+        let unpixelated = world.spawn(Pixelate::default()).id();
+        let despawned = world.spawn(Pixelate::default()).id();
+        for target in [kept, unpixelated, despawned] {
+            let instance_id = world
+                .resource_mut::<SceneSpawner>()
+                .spawn_as_child(Handle::default(), target);
+            world
+                .resource_mut::<SetSceneShadow>()
+                .insert(instance_id, target);
         }
+
+        world.entity_mut(unpixelated).remove::<Pixelate>();
+        world.despawn(despawned);
+        world.run_system_once(set_scene_shadow).unwrap();
+
+        let targets: Vec<_> = world
+            .resource::<SetSceneShadow>()
+            .values()
+            .copied()
+            .collect();
+        assert_eq!(targets, [kept]);
     }
 }
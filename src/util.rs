@@ -1,5 +1,45 @@
+use bevy::math::Affine3A;
+use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
 
 pub(crate) fn get_max_radius(aabb: &Aabb) -> f32 {
     aabb.half_extents.length()
 }
+
+/// Returns how much larger the target is in world space than in its local space.
+/// With a non-uniform scale, this is the largest axis, so that the scaled bounds still cover the target.
+pub(crate) fn get_world_scale(transform: &GlobalTransform) -> f32 {
+    transform.scale().abs().max_element()
+}
+
+/// Returns the center of the bounds of a target in world space.
+/// This is not necessarily the target's origin, e.g. for models whose origin is at their feet.
+pub(crate) fn get_world_center(aabb: &Aabb, transform: &GlobalTransform) -> Vec3 {
+    transform.transform_point(aabb.center.into())
+}
+
+/// Returns the eight corners of `aabb`.
+pub(crate) fn aabb_corners(aabb: &Aabb) -> impl Iterator<Item = Vec3> {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+    [-1.0, 1.0]
+        .into_iter()
+        .flat_map(|x| [-1.0, 1.0].into_iter().map(move |y| (x, y)))
+        .flat_map(|(x, y)| [-1.0, 1.0].into_iter().map(move |z| Vec3::new(x, y, z)))
+        .map(move |corner| center + corner * half_extents)
+}
+
+/// Returns the axis-aligned bounds of `aabb` after transforming it by `transform`.
+pub(crate) fn transform_aabb(aabb: &Aabb, transform: &Affine3A) -> Aabb {
+    let (min, max) = aabb_corners(aabb)
+        .map(|corner| transform.transform_point3(corner))
+        .fold((Vec3::MAX, Vec3::MIN), |(min, max), point| {
+            (min.min(point), max.max(point))
+        });
+    Aabb::from_min_max(min, max)
+}
+
+/// Returns the smallest axis-aligned bounds containing both `a` and `b`.
+pub(crate) fn union_aabb(a: &Aabb, b: &Aabb) -> Aabb {
+    Aabb::from_min_max(a.min().min(b.min()).into(), a.max().max(b.max()).into())
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use bevy::math::Vec3A;
+use bevy::prelude::*;
+use common::{app_with_main_camera, cube, update};
+use pixelate_mesh::prelude::*;
+
+#[test]
+fn bounds_cover_offset_child_meshes() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 10.));
+    let mesh = cube(&mut app);
+    let grandchild = app
+        .world_mut()
+        .spawn((Mesh3d(mesh.clone()), Transform::from_xyz(0., 1., 0.)))
+        .id();
+    // The child has no mesh of its own, but moves and scales the grandchild.
+    let child = app
+        .world_mut()
+        .spawn(Transform::from_xyz(3., 0., 0.).with_scale(Vec3::splat(2.)))
+        .add_child(grandchild)
+        .id();
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .add_child(child)
+        .id();
+    update(&mut app, 5);
+
+    let bounds = app.world().get::<PixelationBounds>(target).unwrap();
+    assert!(
+        bounds.min().abs_diff_eq(Vec3A::new(-0.5, -0.5, -1.), 1e-5),
+        "{bounds:?}"
+    );
+    assert!(
+        bounds.max().abs_diff_eq(Vec3A::new(4., 3., 1.), 1e-5),
+        "{bounds:?}"
+    );
+}
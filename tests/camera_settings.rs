@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::camera::Exposure;
+use common::{app, app_without_plugin, cube, spawn_main_camera, update, MainCamera};
+use pixelate_mesh::prelude::*;
+
+/// Spawns a main camera with the given exposure and a target in front of it.
+fn spawn_scene(app: &mut App, ev100: f32) -> Entity {
+    let main_camera = spawn_main_camera(app, Transform::from_xyz(0., 0., 5.));
+    app.world_mut()
+        .entity_mut(main_camera)
+        .insert(Exposure { ev100 });
+    let mesh = cube(app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()));
+    main_camera
+}
+
+fn pixelation_camera_exposure(app: &mut App) -> f32 {
+    let mut query = app.world_mut().query::<(&Name, &Exposure)>();
+    let (_, exposure) = query
+        .iter(app.world())
+        .find(|(name, _)| name.as_str() == "Pixelation Camera")
+        .unwrap();
+    exposure.ev100
+}
+
+#[test]
+fn inherited_settings_follow_the_main_camera() {
+    let mut app = app();
+    let main_camera = spawn_scene(&mut app, 12.);
+    update(&mut app, 5);
+    assert_eq!(pixelation_camera_exposure(&mut app), 12.);
+
+    app.world_mut()
+        .get_mut::<Exposure>(main_camera)
+        .unwrap()
+        .ev100 = 8.;
+    update(&mut app, 2);
+    assert_eq!(pixelation_camera_exposure(&mut app), 8.);
+}
+
+#[test]
+fn settings_that_are_not_inherited_keep_their_defaults() {
+    let mut app = app_without_plugin();
+    app.add_plugins(
+        PixelateMeshPlugin::<MainCamera>::default()
+            .with_inherited_camera_settings(InheritedCameraSettings::NONE),
+    );
+    spawn_scene(&mut app, 12.);
+    update(&mut app, 5);
+    assert_eq!(
+        pixelation_camera_exposure(&mut app),
+        Exposure::default().ev100
+    );
+}
@@ -0,0 +1,105 @@
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMeshInverseBindposes;
+use bevy::render::render_resource::AsBindGroup;
+use bevy::render::view::VisibilityClass;
+use pixelate_mesh::prelude::*;
+
+#[derive(Component)]
+pub struct MainCamera;
+
+/// A canvas material that only shows the image.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct TestMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub image: Handle<Image>,
+}
+
+impl Material for TestMaterial {}
+
+impl CanvasMaterial for TestMaterial {
+    fn from_canvas_image(image: Handle<Image>, _target: Entity, _pixelate: &Pixelate) -> Self {
+        Self { image }
+    }
+}
+
+/// A headless app with the plugin, but without a main camera.
+pub fn app() -> App {
+    let mut app = app_without_plugin();
+    app.add_plugins(PixelateMeshPlugin::<MainCamera>::default());
+    app
+}
+
+/// A headless app with everything the plugin needs, but without the plugin itself.
+pub fn app_without_plugin() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        bevy::scene::ScenePlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Image>()
+    .init_asset::<SkinnedMeshInverseBindposes>()
+    .register_type::<Mesh3d>()
+    .register_type::<Visibility>()
+    .register_type::<InheritedVisibility>()
+    .register_type::<ViewVisibility>()
+    .register_type::<VisibilityClass>();
+    app
+}
+
+/// A headless app with the plugin and a main camera at `position`, looking along the negative Z axis.
+pub fn app_with_main_camera(position: Vec3) -> App {
+    let mut app = app();
+    spawn_main_camera(&mut app, Transform::from_translation(position));
+    app
+}
+
+pub fn spawn_main_camera(app: &mut App, transform: Transform) -> Entity {
+    app.world_mut()
+        .spawn((MainCamera, Camera3d::default(), transform))
+        .id()
+}
+
+pub fn cube(app: &mut App) -> Handle<Mesh> {
+    app.world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Cuboid::default())
+}
+
+pub fn update(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+pub fn count_named(app: &mut App, name: &str) -> usize {
+    app.world_mut()
+        .query::<&Name>()
+        .iter(app.world())
+        .filter(|entity_name| entity_name.as_str() == name)
+        .count()
+}
+
+/// Returns the transforms of the only entity with the given name.
+pub fn transforms_of(app: &mut App, name: &str) -> (Transform, GlobalTransform) {
+    let mut query = app
+        .world_mut()
+        .query::<(&Name, &Transform, &GlobalTransform)>();
+    let mut matching = query
+        .iter(app.world())
+        .filter(|(entity_name, ..)| entity_name.as_str() == name);
+    let (_, transform, global_transform) = matching.next().unwrap();
+    assert!(matching.next().is_none(), "more than one {name}");
+    (*transform, *global_transform)
+}
+
+pub fn count_meshes(app: &mut App) -> usize {
+    app.world_mut().query::<&Mesh3d>().iter(app.world()).count()
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use common::{app_with_main_camera, count_named, cube, update};
+use pixelate_mesh::prelude::*;
+
+/// Runs `frames` updates and returns every [`PixelationError`] sent during them.
+fn collect_errors(app: &mut App, frames: usize) -> Vec<PixelationError> {
+    let mut cursor = app
+        .world()
+        .resource::<Events<PixelationError>>()
+        .get_cursor_current();
+    let mut errors = Vec::new();
+    for _ in 0..frames {
+        app.update();
+        let events = app.world().resource::<Events<PixelationError>>();
+        errors.extend(cursor.read(events).copied());
+    }
+    errors
+}
+
+fn spawn_scene(app: &mut App, scene_world: World) -> Entity {
+    let scene = app
+        .world_mut()
+        .resource_mut::<Assets<Scene>>()
+        .add(Scene::new(scene_world));
+    app.world_mut()
+        .spawn((Pixelate::splat(16), SceneRoot(scene), Transform::default()))
+        .id()
+}
+
+#[test]
+fn missing_mesh_or_scene_is_reported_once_and_retried() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Transform::default()))
+        .id();
+
+    let errors = collect_errors(&mut app, 5);
+    assert_eq!(
+        errors,
+        [PixelationError {
+            entity: target,
+            reason: PixelationErrorReason::MissingMeshOrScene,
+        }]
+    );
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+
+    let mesh = cube(&mut app);
+    app.world_mut().entity_mut(target).insert(Mesh3d(mesh));
+    update(&mut app, 5);
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+}
+
+#[test]
+fn scene_without_mesh_is_reported() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mut scene_world = World::new();
+    scene_world.spawn(Transform::default());
+    let target = spawn_scene(&mut app, scene_world);
+
+    let errors = collect_errors(&mut app, 5);
+    assert_eq!(
+        errors,
+        [PixelationError {
+            entity: target,
+            reason: PixelationErrorReason::SceneWithoutMesh,
+        }]
+    );
+    assert!(app.world().get_entity(target).is_ok());
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+}
+
+#[test]
+fn mesh_without_positions_is_reported() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        ));
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+
+    let errors = collect_errors(&mut app, 5);
+    assert_eq!(
+        errors,
+        [PixelationError {
+            entity: target,
+            reason: PixelationErrorReason::MeshWithoutBounds,
+        }]
+    );
+    assert!(app.world().get_entity(target).is_ok());
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, ScalingMode};
+use bevy::render::primitives::Aabb;
+use common::{app_with_main_camera, transforms_of, update};
+use pixelate_mesh::prelude::*;
+
+/// Spawns a main camera and a pillar that is four times as tall as it is wide, seen from the front.
+fn app_with_pillar(pixelate: Pixelate) -> App {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 10.));
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Cuboid::new(1., 4., 1.));
+    app.world_mut()
+        .spawn((pixelate, Mesh3d(mesh), Transform::default()));
+    update(&mut app, 5);
+    app
+}
+
+fn canvas_scale(app: &mut App) -> Vec2 {
+    let (canvas, _) = transforms_of(app, "Canvas");
+    canvas.scale.truncate()
+}
+
+fn pixelation_image_size(app: &mut App) -> UVec2 {
+    let mut query = app.world_mut().query::<(&Name, &Camera)>();
+    let (_, camera) = query
+        .iter(app.world())
+        .find(|(name, _)| name.as_str() == "Pixelation Camera")
+        .unwrap();
+    let RenderTarget::Image(image) = &camera.target else {
+        panic!("the pixelation camera does not render to an image");
+    };
+    app.world()
+        .resource::<Assets<Image>>()
+        .get(&image.handle)
+        .unwrap()
+        .size()
+}
+
+#[test]
+fn tight_framing_fits_the_bounds() {
+    let mut app = app_with_pillar(
+        Pixelate {
+            horizontal_pixels: 16,
+            vertical_pixels: 64,
+            ..default()
+        }
+        .with_projection(PixelationProjection::Orthographic)
+        .with_framing(CanvasFraming::Tight),
+    );
+
+    // The canvas is a unit quad, so its scale is half of its size.
+    let scale = canvas_scale(&mut app);
+    assert!(scale.abs_diff_eq(Vec2::new(0.5, 2.), 1e-4), "{scale}");
+
+    let mut query = app.world_mut().query::<(&Name, &Projection)>();
+    let (_, projection) = query
+        .iter(app.world())
+        .find(|(name, _)| name.as_str() == "Pixelation Camera")
+        .unwrap();
+    let Projection::Orthographic(orthographic) = projection else {
+        panic!("expected an orthographic projection, got {projection:?}");
+    };
+    let ScalingMode::Fixed { width, height } = orthographic.scaling_mode else {
+        panic!(
+            "expected a fixed scaling mode, got {:?}",
+            orthographic.scaling_mode
+        );
+    };
+    assert!(Vec2::new(width, height).abs_diff_eq(Vec2::new(1., 4.), 1e-4));
+}
+
+#[test]
+fn tight_framing_keeps_texels_square() {
+    // The image is as tall as the pillar, but four times wider than needed.
+    let mut app = app_with_pillar(
+        Pixelate::splat(64)
+            .with_projection(PixelationProjection::Orthographic)
+            .with_framing(CanvasFraming::Tight),
+    );
+
+    let scale = canvas_scale(&mut app);
+    assert!(scale.abs_diff_eq(Vec2::new(2., 2.), 1e-4), "{scale}");
+}
+
+#[test]
+fn tight_framing_derives_non_square_sizes() {
+    let mut app = app_with_pillar(
+        Pixelate::world_space(16.)
+            .with_projection(PixelationProjection::Orthographic)
+            .with_framing(CanvasFraming::Tight),
+    );
+
+    assert_eq!(pixelation_image_size(&mut app), UVec2::new(16, 64));
+    let scale = canvas_scale(&mut app);
+    assert!(scale.abs_diff_eq(Vec2::new(0.5, 2.), 1e-4), "{scale}");
+}
+
+#[test]
+fn off_center_bounds_are_framed_around_their_center() {
+    // Like a model with its origin at its feet.
+    let bounds = Aabb::from_min_max(Vec3::ZERO, Vec3::new(1., 4., 1.));
+    let mut app = app_with_pillar(Pixelate::splat(64).with_bounds(BoundsMode::Override(bounds)));
+    let center = Vec3::new(0.5, 2., 0.5);
+    let towards_camera = (Vec3::new(0., 0., 10.) - center).normalize();
+
+    let (pixelation_camera, _) = transforms_of(&mut app, "Pixelation Camera");
+    let towards_center = (center - pixelation_camera.translation).normalize();
+    assert!(
+        towards_center.dot(*pixelation_camera.forward()) > 0.999,
+        "the pixelation camera at {} does not look at the center of the bounds",
+        pixelation_camera.translation
+    );
+
+    let (canvas, _) = transforms_of(&mut app, "Canvas");
+    assert!(
+        (canvas.translation - center)
+            .normalize()
+            .dot(towards_camera)
+            > 0.999,
+        "the canvas at {} is not in front of the center of the bounds",
+        canvas.translation
+    );
+}
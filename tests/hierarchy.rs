@@ -0,0 +1,148 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{app, app_with_main_camera, cube, spawn_main_camera, transforms_of, update};
+use pixelate_mesh::prelude::*;
+
+/// Asserts that the canvas sits between `target` and `camera`, and that the pixelation camera looks at `target`.
+fn assert_framed(app: &mut App, target: Vec3, camera: Vec3) {
+    let (canvas, canvas_global) = transforms_of(app, "Canvas");
+    assert!(
+        canvas
+            .translation
+            .abs_diff_eq(canvas_global.translation(), 1e-5),
+        "the canvas' global transform is stale: {canvas:?} {canvas_global:?}"
+    );
+    let towards_camera = (camera - target).normalize();
+    let towards_canvas = (canvas.translation - target).normalize();
+    assert!(
+        towards_canvas.dot(towards_camera) > 0.999,
+        "the canvas at {} is not in front of the target at {target}",
+        canvas.translation
+    );
+
+    let (pixelation_camera, _) = transforms_of(app, "Pixelation Camera");
+    let towards_target = (target - pixelation_camera.translation).normalize();
+    assert!(
+        towards_target.dot(*pixelation_camera.forward()) > 0.999,
+        "the pixelation camera at {} does not look at the target at {target}",
+        pixelation_camera.translation
+    );
+}
+
+#[test]
+fn nested_target_is_followed_in_world_space() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let parent = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(2., 1., 0.).with_rotation(Quat::from_rotation_y(1.)),
+            Visibility::default(),
+        ))
+        .id();
+    let target = app
+        .world_mut()
+        .spawn((
+            Pixelate::splat(16),
+            Mesh3d(mesh),
+            Transform::from_xyz(1., 0., 0.),
+            ChildOf { parent },
+        ))
+        .id();
+    update(&mut app, 5);
+
+    let target_position = app
+        .world()
+        .get::<GlobalTransform>(target)
+        .unwrap()
+        .translation();
+    assert!(!target_position.abs_diff_eq(Vec3::X, 1e-3));
+    assert_framed(&mut app, target_position, Vec3::new(0., 0., 5.));
+
+    // Moving the parent moves the canvas in the same frame.
+    app.world_mut()
+        .get_mut::<Transform>(parent)
+        .unwrap()
+        .translation = Vec3::new(-3., 0., -1.);
+    update(&mut app, 1);
+    let target_position = app
+        .world()
+        .get::<GlobalTransform>(target)
+        .unwrap()
+        .translation();
+    assert_framed(&mut app, target_position, Vec3::new(0., 0., 5.));
+}
+
+#[test]
+fn nested_main_camera_is_followed_in_world_space() {
+    let mut app = app();
+    let rig = app
+        .world_mut()
+        .spawn((Transform::from_xyz(0., 0., 10.), Visibility::default()))
+        .id();
+    let main_camera = spawn_main_camera(&mut app, Transform::from_xyz(0., 0., -5.));
+    app.world_mut()
+        .entity_mut(main_camera)
+        .insert(ChildOf { parent: rig });
+    let mesh = cube(&mut app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()));
+    update(&mut app, 5);
+
+    assert_framed(&mut app, Vec3::ZERO, Vec3::new(0., 0., 5.));
+
+    // Moving the rig moves the camera, so the canvas turns to face it.
+    app.world_mut()
+        .get_mut::<Transform>(rig)
+        .unwrap()
+        .translation = Vec3::new(5., 0., 5.);
+    update(&mut app, 1);
+    assert_framed(&mut app, Vec3::ZERO, Vec3::new(5., 0., 0.));
+}
+
+#[test]
+fn canvas_follows_world_scale() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 10.));
+    let mesh = cube(&mut app);
+    let parent = app
+        .world_mut()
+        .spawn((
+            Transform::from_scale(Vec3::splat(2.)),
+            Visibility::default(),
+        ))
+        .id();
+    let target = app
+        .world_mut()
+        .spawn((
+            Pixelate::splat(16),
+            Mesh3d(mesh),
+            Transform::from_scale(Vec3::new(1., 1.5, 1.)),
+            ChildOf { parent },
+        ))
+        .id();
+    update(&mut app, 5);
+
+    // The unit cube reaches `sqrt(3) / 2` from its center, scaled by the largest axis.
+    let radius = 3f32.sqrt() / 2.;
+    let (canvas, _) = transforms_of(&mut app, "Canvas");
+    assert!(
+        canvas.scale.abs_diff_eq(Vec3::splat(radius * 3.), 1e-4),
+        "{canvas:?}"
+    );
+
+    // A growing target grows its canvas in the same frame.
+    app.world_mut().get_mut::<Transform>(target).unwrap().scale = Vec3::splat(2.);
+    update(&mut app, 1);
+    let (canvas, _) = transforms_of(&mut app, "Canvas");
+    assert!(
+        canvas.scale.abs_diff_eq(Vec3::splat(radius * 4.), 1e-4),
+        "{canvas:?}"
+    );
+    assert!(
+        canvas
+            .translation
+            .abs_diff_eq(Vec3::new(0., 0., radius * 4.), 1e-4),
+        "{canvas:?}"
+    );
+}
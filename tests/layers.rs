@@ -0,0 +1,95 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use common::{cube, spawn_main_camera, update};
+use pixelate_mesh::prelude::*;
+
+/// An app that mirrors lights onto targets with their own layer.
+fn app() -> App {
+    let mut app = common::app();
+    let mut layers = app.world_mut().resource_mut::<PixelationLayers>();
+    layers.per_target = true;
+    layers.mirror_lights = true;
+    spawn_main_camera(&mut app, Transform::from_xyz(0., 0., 5.));
+    app
+}
+
+fn spawn_target(app: &mut App) -> Entity {
+    let mesh = cube(app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id()
+}
+
+fn target_layers(app: &App, target: Entity) -> RenderLayers {
+    app.world()
+        .get::<PixelationRenderLayers>(target)
+        .expect("the target should be pixelated")
+        .0
+        .clone()
+}
+
+#[test]
+fn light_on_main_layer_mirrors_target_layers() {
+    let mut app = app();
+    let light = app.world_mut().spawn(DirectionalLight::default()).id();
+    let target = spawn_target(&mut app);
+    update(&mut app, 5);
+
+    let target_layers = target_layers(&app, target);
+    assert_eq!(
+        app.world().get::<RenderLayers>(light),
+        Some(
+            &RenderLayers::layer(0)
+                .union(&PIXELATION_RENDER_LAYERS)
+                .union(&target_layers)
+        )
+    );
+
+    app.world_mut().entity_mut(target).remove::<Pixelate>();
+    update(&mut app, 2);
+    assert_eq!(
+        app.world().get::<RenderLayers>(light),
+        Some(&RenderLayers::layer(0).union(&PIXELATION_RENDER_LAYERS))
+    );
+}
+
+#[test]
+fn disabling_mirror_lights_restores_light_layers() {
+    let mut app = app();
+    let light = app
+        .world_mut()
+        .spawn((PointLight::default(), RenderLayers::layer(0)))
+        .id();
+    spawn_target(&mut app);
+    update(&mut app, 5);
+    assert_ne!(
+        app.world().get::<RenderLayers>(light),
+        Some(&RenderLayers::layer(0))
+    );
+
+    app.world_mut()
+        .resource_mut::<PixelationLayers>()
+        .mirror_lights = false;
+    update(&mut app, 2);
+    assert_eq!(
+        app.world().get::<RenderLayers>(light),
+        Some(&RenderLayers::layer(0))
+    );
+}
+
+#[test]
+fn light_only_on_plugin_layers_is_left_alone() {
+    let mut app = app();
+    let target = spawn_target(&mut app);
+    update(&mut app, 5);
+
+    let target_layers = target_layers(&app, target);
+    let light = app
+        .world_mut()
+        .spawn((SpotLight::default(), target_layers.clone()))
+        .id();
+    update(&mut app, 2);
+    assert_eq!(app.world().get::<RenderLayers>(light), Some(&target_layers));
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use common::{
+    app, app_without_plugin, count_named, cube, spawn_main_camera, update, MainCamera, TestMaterial,
+};
+use pixelate_mesh::prelude::*;
+
+/// The render layers of all canvas meshes.
+fn canvas_mesh_layers(app: &mut App) -> Vec<RenderLayers> {
+    let mut query = app.world_mut().query::<(&Name, &RenderLayers)>();
+    query
+        .iter(app.world())
+        .filter(|(name, _)| name.as_str() == "Canvas Mesh")
+        .map(|(_, render_layers)| render_layers.clone())
+        .collect()
+}
+
+#[test]
+fn every_main_camera_gets_its_own_pair() {
+    let mut app = app();
+    let left = spawn_main_camera(&mut app, Transform::from_xyz(-2., 0., 5.));
+    let right = spawn_main_camera(&mut app, Transform::from_xyz(2., 0., 5.));
+    let mesh = cube(&mut app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()));
+    update(&mut app, 5);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 2);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 2);
+
+    // Each main camera sees only its own canvas.
+    let left_layers = app.world().get::<RenderLayers>(left).unwrap().clone();
+    let right_layers = app.world().get::<RenderLayers>(right).unwrap().clone();
+    assert!(left_layers.intersects(&RenderLayers::layer(0)));
+    assert!(right_layers.intersects(&RenderLayers::layer(0)));
+    let canvas_layers = canvas_mesh_layers(&mut app);
+    assert_eq!(canvas_layers.len(), 2);
+    for layers in &canvas_layers {
+        assert!(
+            left_layers.intersects(layers) != right_layers.intersects(layers),
+            "{layers:?} is seen by {left_layers:?} and {right_layers:?}"
+        );
+    }
+}
+
+#[test]
+fn removing_a_main_camera_removes_its_pair_and_layer() {
+    let mut app = app();
+    let kept = spawn_main_camera(&mut app, Transform::from_xyz(-2., 0., 5.));
+    let removed = spawn_main_camera(&mut app, Transform::from_xyz(2., 0., 5.));
+    let mesh = cube(&mut app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()));
+    update(&mut app, 5);
+    assert_eq!(count_named(&mut app, "Canvas"), 2);
+
+    app.world_mut().entity_mut(removed).remove::<MainCamera>();
+    update(&mut app, 2);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 1);
+    assert_eq!(
+        app.world().get::<RenderLayers>(removed),
+        Some(&RenderLayers::layer(0))
+    );
+    let kept_layers = app.world().get::<RenderLayers>(kept).unwrap().clone();
+    assert_eq!(canvas_mesh_layers(&mut app).len(), 1);
+    assert!(kept_layers.intersects(&canvas_mesh_layers(&mut app)[0]));
+}
+
+#[test]
+fn pixelated_entities_are_never_main_cameras() {
+    let mut app = app();
+    spawn_main_camera(&mut app, Transform::from_xyz(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((
+            Pixelate::splat(16),
+            Mesh3d(mesh),
+            MainCamera,
+            Camera3d::default(),
+            Transform::default(),
+        ))
+        .id();
+    update(&mut app, 5);
+
+    // The entity is an invalid target, and it gets neither pairs of its own nor a canvas layer.
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 0);
+
+    // Its original render layers are restored without a leftover canvas layer.
+    app.world_mut()
+        .entity_mut(target)
+        .remove::<(Pixelate, MainCamera)>();
+    update(&mut app, 2);
+    let restored = app.world().get::<RenderLayers>(target);
+    assert!(
+        restored.is_none_or(|layers| *layers == RenderLayers::layer(0)),
+        "{restored:?}"
+    );
+}
+
+/// The main camera component of a second plugin instance.
+#[derive(Component)]
+struct OtherCamera;
+
+/// Which materials each canvas mesh has, as `(standard, test)` pairs.
+fn canvas_materials(app: &mut App) -> Vec<(bool, bool)> {
+    let mut query = app.world_mut().query::<(
+        &Name,
+        Has<MeshMaterial3d<StandardMaterial>>,
+        Has<MeshMaterial3d<TestMaterial>>,
+    )>();
+    let mut materials: Vec<_> = query
+        .iter(app.world())
+        .filter(|(name, ..)| name.as_str() == "Canvas Mesh")
+        .map(|(_, standard, test)| (standard, test))
+        .collect();
+    materials.sort();
+    materials
+}
+
+#[test]
+fn plugin_instances_only_handle_their_own_cameras() {
+    let mut app = app_without_plugin();
+    app.init_asset::<TestMaterial>().add_plugins((
+        PixelateMeshPlugin::<MainCamera>::default(),
+        PixelateMeshPlugin::<OtherCamera, TestMaterial>::default(),
+    ));
+    spawn_main_camera(&mut app, Transform::from_xyz(-2., 0., 5.));
+    app.world_mut().spawn((
+        OtherCamera,
+        Camera3d::default(),
+        Transform::from_xyz(2., 0., 5.),
+    ));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+    update(&mut app, 5);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 2);
+    assert_eq!(count_named(&mut app, "Pixelation Shadow"), 1);
+    assert_eq!(canvas_materials(&mut app), [(false, true), (true, false)]);
+
+    // Changing the target updates every canvas material, but only by the instance the canvas belongs to.
+    *app.world_mut().get_mut::<Pixelate>(target).unwrap() = Pixelate::splat(32);
+    update(&mut app, 2);
+    assert_eq!(canvas_materials(&mut app), [(false, true), (true, false)]);
+}
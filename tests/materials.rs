@@ -0,0 +1,53 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use common::{app_without_plugin, cube, spawn_main_camera, update, MainCamera, TestMaterial};
+use pixelate_mesh::prelude::*;
+
+/// Returns the image the pixelation camera renders to and the image shown by the canvas material.
+fn images(app: &mut App) -> (Handle<Image>, Handle<Image>) {
+    let mut camera_query = app.world_mut().query::<(&Name, &Camera)>();
+    let (_, camera) = camera_query
+        .iter(app.world())
+        .find(|(name, _)| name.as_str() == "Pixelation Camera")
+        .unwrap();
+    let RenderTarget::Image(target) = &camera.target else {
+        panic!("the pixelation camera does not render to an image");
+    };
+    let rendered = target.handle.clone();
+
+    let mut material_query = app.world_mut().query::<&MeshMaterial3d<TestMaterial>>();
+    let material = material_query.single(app.world()).unwrap();
+    let shown = app
+        .world()
+        .resource::<Assets<TestMaterial>>()
+        .get(material)
+        .unwrap()
+        .image
+        .clone();
+    (rendered, shown)
+}
+
+#[test]
+fn custom_materials_show_the_resized_image() {
+    let mut app = app_without_plugin();
+    app.init_asset::<TestMaterial>()
+        .add_plugins(PixelateMeshPlugin::<MainCamera, TestMaterial>::default());
+    spawn_main_camera(&mut app, Transform::from_xyz(0., 0., 10.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+    update(&mut app, 5);
+    let (rendered, shown) = images(&mut app);
+    assert_eq!(rendered, shown);
+
+    *app.world_mut().get_mut::<Pixelate>(target).unwrap() = Pixelate::splat(32);
+    update(&mut app, 5);
+    let (rendered, shown) = images(&mut app);
+    assert_eq!(rendered, shown);
+    let image = app.world().resource::<Assets<Image>>().get(&shown).unwrap();
+    assert_eq!(image.size(), UVec2::splat(32));
+}
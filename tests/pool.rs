@@ -0,0 +1,77 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{app_with_main_camera, cube, update};
+use pixelate_mesh::prelude::*;
+
+fn spawn_target(app: &mut App) -> Entity {
+    let mesh = cube(app);
+    app.world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id()
+}
+
+fn resize(app: &mut App, target: Entity, size: u32) {
+    *app.world_mut().get_mut::<Pixelate>(target).unwrap() = Pixelate::splat(size);
+    update(app, 2);
+}
+
+fn stats(app: &App) -> RenderTargetPoolStats {
+    app.world().resource::<RenderTargetPool>().stats()
+}
+
+#[test]
+fn resizing_back_reuses_the_pooled_texture() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let target = spawn_target(&mut app);
+    update(&mut app, 5);
+    assert_eq!(
+        stats(&app),
+        RenderTargetPoolStats {
+            in_use: 1,
+            free: 0,
+            created: 1,
+            reused: 0,
+        }
+    );
+
+    resize(&mut app, target, 32);
+    assert_eq!(
+        stats(&app),
+        RenderTargetPoolStats {
+            in_use: 1,
+            free: 1,
+            created: 2,
+            reused: 0,
+        }
+    );
+
+    resize(&mut app, target, 16);
+    assert_eq!(
+        stats(&app),
+        RenderTargetPoolStats {
+            in_use: 1,
+            free: 1,
+            created: 2,
+            reused: 1,
+        }
+    );
+}
+
+#[test]
+fn idle_textures_are_freed_after_max_idle_frames() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    app.world_mut()
+        .resource_mut::<RenderTargetPool>()
+        .max_idle_frames = 3;
+    let target = spawn_target(&mut app);
+    update(&mut app, 5);
+
+    resize(&mut app, target, 32);
+    assert_eq!(stats(&app).free, 1);
+    update(&mut app, 1);
+    assert_eq!(stats(&app).free, 1);
+    update(&mut app, 2);
+    assert_eq!(stats(&app).free, 0);
+    assert_eq!(stats(&app).in_use, 1);
+}
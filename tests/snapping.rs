@@ -0,0 +1,58 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{app, cube, spawn_main_camera, transforms_of, update};
+use pixelate_mesh::prelude::*;
+use std::f32::consts::PI;
+
+#[test]
+fn moving_less_than_a_texel_keeps_the_camera_on_its_texel() {
+    let mut app = app();
+    let main_camera = spawn_main_camera(&mut app, Transform::from_xyz(0., 0., 10.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((
+            Pixelate::splat(32).with_texel_snapping(),
+            Mesh3d(mesh),
+            Transform::default(),
+        ))
+        .id();
+    update(&mut app, 5);
+    let (camera_before, _) = transforms_of(&mut app, "Pixelation Camera");
+    let (canvas_before, _) = transforms_of(&mut app, "Canvas");
+
+    // The pixelation camera looks at the target with the default 45° field of view and renders 32 texels.
+    let distance = camera_before.translation.length();
+    let frame_height = 2. * distance * (PI / 8.).tan();
+    let texel_size = frame_height / 32.;
+
+    // Move the target and the main camera together, so only the snapping moves anything relative to them.
+    let step = Vec3::X * texel_size / 4.;
+    app.world_mut()
+        .get_mut::<Transform>(target)
+        .unwrap()
+        .translation += step;
+    app.world_mut()
+        .get_mut::<Transform>(main_camera)
+        .unwrap()
+        .translation += step;
+    update(&mut app, 5);
+    let (camera_after, _) = transforms_of(&mut app, "Pixelation Camera");
+    let (canvas_after, _) = transforms_of(&mut app, "Canvas");
+
+    assert!(
+        camera_after
+            .translation
+            .abs_diff_eq(camera_before.translation, 1e-5),
+        "the pixelation camera left its texel: {camera_before:?} {camera_after:?}"
+    );
+    // The canvas follows the target, minus the quarter texel the image is now shifted by.
+    // The canvas is as wide as the bounding sphere of the unit cube.
+    let canvas_size = 3f32.sqrt();
+    let expected = step - step * canvas_size / frame_height;
+    assert!(
+        (canvas_after.translation - canvas_before.translation).abs_diff_eq(expected, 1e-5),
+        "the canvas did not compensate for the snapping: {canvas_before:?} {canvas_after:?}"
+    );
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use common::{app_with_main_camera, count_meshes, count_named, cube, update};
+use pixelate_mesh::prelude::*;
+
+#[test]
+fn removing_pixelate_restores_mesh_target() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let child = app
+        .world_mut()
+        .spawn((Mesh3d(mesh.clone()), Transform::from_xyz(1., 0., 0.)))
+        .id();
+    let target = app
+        .world_mut()
+        .spawn((
+            Pixelate::splat(16),
+            Mesh3d(mesh),
+            Transform::default(),
+            RenderLayers::layer(5),
+        ))
+        .add_child(child)
+        .id();
+    let meshes_before = count_meshes(&mut app);
+    update(&mut app, 5);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 1);
+    assert_eq!(count_named(&mut app, "Pixelation Shadow"), 1);
+    assert_eq!(
+        app.world().get::<RenderLayers>(target),
+        Some(&PIXELATION_RENDER_LAYERS)
+    );
+    assert_eq!(
+        app.world().get::<RenderLayers>(child),
+        Some(&PIXELATION_RENDER_LAYERS)
+    );
+    assert_eq!(app.world().resource::<RenderTargetPool>().stats().in_use, 1);
+
+    app.world_mut().entity_mut(target).remove::<Pixelate>();
+    update(&mut app, 2);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+    assert_eq!(count_named(&mut app, "Canvas Mesh"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Shadow"), 0);
+    assert_eq!(count_meshes(&mut app), meshes_before);
+    assert_eq!(
+        app.world().get::<RenderLayers>(target),
+        Some(&RenderLayers::layer(5))
+    );
+    assert_eq!(app.world().get::<RenderLayers>(child), None);
+    assert!(app.world().get::<PixelationBounds>(target).is_none());
+    assert!(app.world().get::<PixelationRenderLayers>(target).is_none());
+    let stats = app.world().resource::<RenderTargetPool>().stats();
+    assert_eq!(stats.in_use, 0);
+    assert_eq!(stats.free, 1);
+}
+
+#[test]
+fn pixelate_can_be_toggled() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+    for _ in 0..3 {
+        update(&mut app, 5);
+        assert_eq!(count_named(&mut app, "Canvas"), 1);
+        assert_eq!(count_named(&mut app, "Pixelation Shadow"), 1);
+
+        app.world_mut().entity_mut(target).remove::<Pixelate>();
+        update(&mut app, 2);
+        assert_eq!(count_named(&mut app, "Canvas"), 0);
+        assert_eq!(count_named(&mut app, "Pixelation Shadow"), 0);
+        assert_eq!(app.world().get::<RenderLayers>(target), None);
+
+        app.world_mut()
+            .entity_mut(target)
+            .insert(Pixelate::splat(16));
+    }
+}
+
+#[test]
+fn removing_pixelate_despawns_shadow_scene() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let mut scene_world = World::new();
+    scene_world.spawn((Mesh3d(mesh), Transform::default()));
+    let scene = app
+        .world_mut()
+        .resource_mut::<Assets<Scene>>()
+        .add(Scene::new(scene_world));
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), SceneRoot(scene), Transform::default()))
+        .id();
+    update(&mut app, 2);
+    let meshes_before = count_meshes(&mut app);
+    update(&mut app, 5);
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+    assert!(count_meshes(&mut app) > meshes_before);
+
+    app.world_mut().entity_mut(target).remove::<Pixelate>();
+    update(&mut app, 2);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 0);
+    assert_eq!(count_meshes(&mut app), meshes_before);
+    let mut scene_meshes = app
+        .world_mut()
+        .query_filtered::<Option<&RenderLayers>, With<Mesh3d>>();
+    assert!(scene_meshes
+        .iter(app.world())
+        .all(|layers| layers.is_none()));
+}
+
+#[test]
+fn despawning_target_despawns_canvas() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+    update(&mut app, 5);
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+
+    app.world_mut().entity_mut(target).despawn();
+    update(&mut app, 2);
+
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 0);
+    assert_eq!(app.world().resource::<RenderTargetPool>().stats().in_use, 0);
+}
+
+#[test]
+fn invalid_target_is_left_intact() {
+    let mut app = app_with_main_camera(Vec3::new(0., 0., 5.));
+    let mesh = cube(&mut app);
+    let target = app
+        .world_mut()
+        .spawn((Pixelate::splat(16), Mesh3d(mesh), Transform::default()))
+        .id();
+    update(&mut app, 5);
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+
+    app.world_mut()
+        .entity_mut(target)
+        .remove::<GlobalTransform>();
+    update(&mut app, 1);
+
+    assert!(app.world().get_entity(target).is_ok());
+    assert_eq!(count_named(&mut app, "Canvas"), 0);
+    assert_eq!(count_named(&mut app, "Pixelation Camera"), 0);
+    let errors: Vec<_> = app
+        .world()
+        .resource::<Events<PixelationError>>()
+        .iter_current_update_events()
+        .copied()
+        .collect();
+    assert_eq!(
+        errors,
+        [PixelationError {
+            entity: target,
+            reason: PixelationErrorReason::InvalidTarget,
+        }]
+    );
+
+    app.world_mut()
+        .entity_mut(target)
+        .insert(GlobalTransform::default());
+    update(&mut app, 2);
+    assert_eq!(count_named(&mut app, "Canvas"), 1);
+}